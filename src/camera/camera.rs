@@ -1,4 +1,6 @@
 use crate::camera::*;
+use crate::input::*;
+use crate::physics::*;
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::*;
 
@@ -9,10 +11,27 @@ pub struct Camera {
     move_sens: f32,
     look_sens: f32,
     zoom_sens: f32,
+    stick_sens: f32,
+    stick: StickResponse,
 }
 
-#[derive(Component)]
-pub struct CameraTarget();
+#[derive(Component, Default)]
+pub struct CameraTarget {
+    offset: Vec3,
+}
+
+impl CameraTarget {
+    pub fn new(offset: Vec3) -> Self {
+        Self { offset }
+    }
+    /// Point the camera looks at, relative to the target's translation
+    pub fn offset(&self) -> Vec3 {
+        self.offset
+    }
+    pub fn set_offset(&mut self, offset: Vec3) {
+        self.offset = offset;
+    }
+}
 
 impl Default for Camera {
     fn default() -> Self {
@@ -22,6 +41,8 @@ impl Default for Camera {
             move_sens: 0.005,
             look_sens: 0.005,
             zoom_sens: 0.1,
+            stick_sens: 3.0,
+            stick: StickResponse::new(0.15, 2.0),
         }
     }
 }
@@ -64,6 +85,28 @@ impl Camera {
     pub fn set_zoom_sens(&mut self, zoom_sens: f32) {
         self.zoom_sens = zoom_sens;
     }
+    /// Look speed at full stick tilt, in radians per second
+    pub fn stick_sens(&self) -> f32 {
+        self.stick_sens
+    }
+    pub fn set_stick_sens(&mut self, stick_sens: f32) {
+        self.stick_sens = stick_sens;
+    }
+    pub fn stick(&self) -> &StickResponse {
+        &self.stick
+    }
+    pub fn set_stick(&mut self, stick: StickResponse) {
+        self.stick = stick;
+    }
+
+    /// Look rotation from mouse motion and the look stick, in radians
+    fn look_delta(&self, actions: &ActionState<CameraAction>, mouse_sens: f32, dt: f32) -> Vec2 {
+        let mouse = actions.axis_pair(CameraAction::Pan).unwrap_or_default();
+        let stick = actions.axis_pair(CameraAction::Look).unwrap_or_default();
+        let stick = self.stick.apply(stick.xy()) * Vec2::new(1.0, -1.0);
+
+        mouse.xy() * mouse_sens + stick * self.stick_sens * dt
+    }
 }
 
 pub struct BoxyCameraPlugin;
@@ -73,6 +116,8 @@ impl Plugin for BoxyCameraPlugin {
             .add_state::<CameraState>()
             .add_plugins(InputManagerPlugin::<CameraAction>::default())
             .add_plugins(InputManagerPlugin::<CameraMovement>::default())
+            .add_plugins(InputBindingsPlugin::<CameraAction>::default())
+            .add_plugins(InputBindingsPlugin::<CameraMovement>::default())
             .add_systems(Update, update_camera_state)
             .add_systems(
                 Update,
@@ -84,7 +129,9 @@ impl Plugin for BoxyCameraPlugin {
             )
             .add_systems(
                 Update,
-                update_camera_orb.run_if(in_state(CameraState::ThirdPerson)),
+                update_camera_orb
+                    .after(RenderInterpolationSet::Apply)
+                    .run_if(in_state(CameraState::ThirdPerson)),
             )
             .add_systems(
                 Update,
@@ -104,7 +151,11 @@ impl Plugin for BoxyCameraPlugin {
     }
 }
 
-fn spawn_camera(mut commands: Commands) {
+fn spawn_camera(
+    mut commands: Commands,
+    actions: Res<InputBindings<CameraAction>>,
+    movement: Res<InputBindings<CameraMovement>>,
+) {
     let translation = Vec3::new(-2.0, 2.5, 5.0);
     let radius = translation.length();
 
@@ -118,25 +169,11 @@ fn spawn_camera(mut commands: Commands) {
             ..Default::default()
         })
         .insert(InputManagerBundle::<CameraAction> {
-            input_map: InputMap::default()
-                .insert(DualAxis::mouse_motion(), CameraAction::Pan)
-                .insert(DualAxis::mouse_wheel(), CameraAction::Zoom)
-                .insert(MouseButton::Right, CameraAction::MoveTrigger)
-                .insert(MouseButton::Middle, CameraAction::PanTrigger)
-                .insert(KeyCode::ShiftLeft, CameraAction::SpeedTrigger)
-                .insert(KeyCode::C, CameraAction::ModeCycleTrigger)
-                .build(),
+            input_map: actions.0.clone(),
             action_state: ActionState::default(),
         })
         .insert(InputManagerBundle::<CameraMovement> {
-            input_map: InputMap::default()
-                .insert(KeyCode::W, CameraMovement::Forward)
-                .insert(KeyCode::A, CameraMovement::Left)
-                .insert(KeyCode::S, CameraMovement::Back)
-                .insert(KeyCode::D, CameraMovement::Right)
-                .insert(KeyCode::Space, CameraMovement::Up)
-                .insert(KeyCode::ControlLeft, CameraMovement::Down)
-                .build(),
+            input_map: movement.0.clone(),
             action_state: ActionState::default(),
         });
 }
@@ -187,35 +224,36 @@ fn update_camera_zoom(mut q: Query<(&mut Projection, &Camera, &ActionState<Camer
 fn update_camera_rot(
     mut q: Query<(&mut Transform, &Camera, &ActionState<CameraAction>)>,
     state: Res<State<CameraState>>,
+    time: Res<Time>,
 ) {
     let (mut transform, camera, actions) = q.single_mut();
-    let motion = actions.axis_pair(CameraAction::Pan).unwrap();
+    let motion = camera.look_delta(actions, camera.look_sens, time.delta_seconds());
     let triggered = actions.pressed(CameraAction::MoveTrigger);
     let state = *state.get();
 
     if state == CameraState::FreeFloat || state == CameraState::FirstPerson || triggered {
-        transform.rotation =
-            Quat::from_rotation_y(-motion.x() * camera.look_sens) * transform.rotation;
-        transform.rotation *= Quat::from_rotation_x(-motion.y() * camera.look_sens);
+        transform.rotation = Quat::from_rotation_y(-motion.x) * transform.rotation;
+        transform.rotation *= Quat::from_rotation_x(-motion.y);
     }
 }
 
 fn update_camera_orb(
     mut camera: Query<(&mut Transform, &Camera, &ActionState<CameraAction>)>,
     target: Query<(&Transform, &CameraTarget, Without<Camera>)>,
+    time: Res<Time>,
 ) {
     let (mut transform, camera, actions) = camera.single_mut();
-    let motion = actions.axis_pair(CameraAction::Pan).unwrap_or_default();
-    let position = if let Ok((position, _, _)) = target.get_single() {
-        position.translation
+    let sensitivity = 0.01; // Adjust the sensitivity based on your preference
+    let motion = camera.look_delta(actions, sensitivity, time.delta_seconds());
+    let position = if let Ok((position, target, _)) = target.get_single() {
+        position.translation + target.offset()
     } else {
         camera.focus
     };
     let radius = camera.radius;
 
-    let sensitivity = 0.01; // Adjust the sensitivity based on your preference
-    let delta_yaw = -sensitivity * motion.x();
-    let delta_pitch = sensitivity * motion.y();
+    let delta_yaw = -motion.x;
+    let delta_pitch = motion.y;
     let camera_local_x = transform.local_x();
     let camera_local_z = transform.local_z();
 
@@ -1,7 +1,9 @@
-use leafwing_input_manager::prelude::*;
+use crate::input::*;
 use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Actionlike, Reflect, Clone, Hash, Debug, Copy, PartialEq, Eq)]
+#[derive(Actionlike, Reflect, Clone, Hash, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CameraAction {
     Rotate,
     MoveTrigger,
@@ -10,4 +12,24 @@ pub enum CameraAction {
     Zoom,
     SpeedTrigger,
     ModeCycleTrigger,
-}
\ No newline at end of file
+    /// Analog stick look
+    Look,
+}
+
+impl DefaultBindings for CameraAction {
+    fn default_input_map() -> InputMap<Self> {
+        InputMap::default()
+            .insert(DualAxis::mouse_motion(), CameraAction::Pan)
+            .insert(DualAxis::mouse_wheel(), CameraAction::Zoom)
+            .insert(MouseButton::Right, CameraAction::MoveTrigger)
+            .insert(MouseButton::Middle, CameraAction::PanTrigger)
+            .insert(KeyCode::ShiftLeft, CameraAction::SpeedTrigger)
+            .insert(KeyCode::C, CameraAction::ModeCycleTrigger)
+            .insert(
+                StickResponse::raw(DualAxis::right_stick()),
+                CameraAction::Look,
+            )
+            .insert(GamepadButtonType::Select, CameraAction::ModeCycleTrigger)
+            .build()
+    }
+}
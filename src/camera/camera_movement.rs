@@ -1,7 +1,9 @@
-use leafwing_input_manager::prelude::*;
+use crate::input::*;
 use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Actionlike, Clone, Reflect, Hash, Debug, Copy, PartialEq, Eq)]
+#[derive(Actionlike, Clone, Reflect, Hash, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CameraMovement {
     Left,
     Right,
@@ -22,4 +24,17 @@ impl CameraMovement {
             CameraMovement::Forward => Vec3::NEG_Z,
         }
     }
-}
\ No newline at end of file
+}
+
+impl DefaultBindings for CameraMovement {
+    fn default_input_map() -> InputMap<Self> {
+        InputMap::default()
+            .insert(KeyCode::W, CameraMovement::Forward)
+            .insert(KeyCode::A, CameraMovement::Left)
+            .insert(KeyCode::S, CameraMovement::Back)
+            .insert(KeyCode::D, CameraMovement::Right)
+            .insert(KeyCode::Space, CameraMovement::Up)
+            .insert(KeyCode::ControlLeft, CameraMovement::Down)
+            .build()
+    }
+}
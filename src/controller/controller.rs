@@ -4,50 +4,423 @@ use bevy_rapier3d::prelude::*;
 use leafwing_input_manager::prelude::*;
 use std::ops::{Add, Mul};
 
-pub struct BoxyControllerPlugin;
+pub struct BoxyControllerPlugin {
+    /// Spawn a default player character on startup
+    pub spawn_player: bool,
+    /// Visible body of the default player, `None` leaves it invisible
+    pub player_model: Option<CharacterModel>,
+    /// Draw [`ControllerGizmos`] over every character
+    pub gizmos: bool,
+}
+
+impl Default for BoxyControllerPlugin {
+    fn default() -> Self {
+        Self {
+            spawn_player: true,
+            player_model: Some(CharacterModel::default()),
+            gizmos: false,
+        }
+    }
+}
+
 impl Plugin for BoxyControllerPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<ControllerSpeed>()
-            .add_state::<ControllerState>()
-            .add_plugins(InputManagerPlugin::<CharacterMovement>::default())
+        if self.spawn_player {
+            let model = self.player_model.clone();
+            app.add_systems(
+                Startup,
+                move |mut commands: Commands,
+                      movement: Res<InputBindings<CharacterMovement>>,
+                      actions: Res<InputBindings<CharacterActions>>| {
+                    let player = spawn_player(&mut commands, &movement, &actions);
+                    if let Some(model) = model.clone() {
+                        commands.entity(player).insert(model);
+                    }
+                },
+            );
+        }
+
+        if self.gizmos {
+            app.init_resource::<ControllerGizmos>().add_systems(
+                Update,
+                (
+                    toggle_controller_gizmos,
+                    draw_controller_gizmos,
+                    update_state_labels,
+                )
+                    .chain()
+                    .after(RenderInterpolationSet::Apply),
+            );
+        }
+
+        app.add_plugins(InputManagerPlugin::<CharacterMovement>::default())
             .add_plugins(InputManagerPlugin::<CharacterActions>::default())
-            .add_systems(Startup, spawn_player)
+            .add_plugins(InputBindingsPlugin::<CharacterMovement>::default())
+            .add_plugins(InputBindingsPlugin::<CharacterActions>::default())
+            .add_event::<Dashed>()
+            .add_event::<AirJumped>()
+            .add_event::<AbilityRecharged>()
+            .add_event::<StaminaChanged>()
+            .add_event::<StaminaDepleted>()
+            .add_event::<StaminaRecovered>()
+            .add_event::<ControllerStateChanged>()
+            .add_event::<Jumped>()
+            .add_event::<Landed>()
+            .add_event::<SlideStarted>()
+            .add_event::<SlideStopped>()
+            .add_event::<Footstep>()
+            .init_resource::<ControllerStateMachine>()
             .add_systems(
-                Update,
+                PreUpdate,
+                latch_controller_input
+                    .after(leafwing_input_manager::plugin::InputManagerSystem::Update),
+            )
+            .configure_sets(
+                FixedUpdate,
+                (
+                    ControllerSet::State,
+                    ControllerSet::Forces,
+                    ControllerSet::Apply,
+                )
+                    .chain()
+                    .after(RenderInterpolationSet::Restore)
+                    .before(PhysicsSet::SyncBackend),
+            )
+            .add_systems(
+                FixedUpdate,
                 (
+                    update_ground_probe,
+                    update_footsteps,
+                    update_wall_probe,
+                    update_ledge_probe,
+                    update_climb_probe,
+                    update_fluid_probe,
+                    update_player_platform,
+                    update_headroom_probe,
+                    update_controller_timers,
+                    update_player_abilities,
+                    update_stamina,
+                    update_player_state,
+                    update_player_crouch,
                     update_player_speed,
+                )
+                    .chain()
+                    .in_set(ControllerSet::State),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
                     update_gravity_force,
                     update_action_force,
                     update_movement_force,
-                ),
+                    update_slide_force,
+                    update_wall_force.after(update_movement_force),
+                    update_climb_force.after(update_movement_force),
+                )
+                    .in_set(ControllerSet::Forces),
             )
-            .add_systems(Update, (update_player_pos, update_player_state));
+            .add_systems(
+                FixedUpdate,
+                (
+                    update_player_pos,
+                    update_player_rot,
+                    update_player_eye,
+                    update_player_mantle,
+                )
+                    .in_set(ControllerSet::Apply),
+            )
+            .add_systems(
+                FixedUpdate,
+                consume_controller_input.after(ControllerSet::Apply),
+            )
+            .add_systems(
+                Update,
+                (update_animation_params, update_character_animations)
+                    .chain()
+                    .after(RenderInterpolationSet::Apply),
+            )
+            .add_systems(
+                Update,
+                (
+                    despawn_removed_character_models,
+                    spawn_character_models,
+                    update_character_models,
+                )
+                    .chain(),
+            );
     }
 }
 
+/// Ordered stages of a controller step, run in `FixedUpdate` before Rapier
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ControllerSet {
+    /// State transitions and speed selection
+    State,
+    /// Gravity, action and movement forces
+    Forces,
+    /// Moving and rotating the character
+    Apply,
+}
+
 #[derive(Component)]
 pub struct MovementController {
     speed: ControllerSpeedSettings,
+    acceleration: ControllerAccelerationSettings,
     forces: ControllerForces,
     jump_force: f32,
+    jump: ControllerJumpSettings,
+    jump_timers: ControllerJumpTimers,
     grounded: bool,
+    ground: ControllerGroundSettings,
     height: f32,
+    radius: f32,
+    crouch: ControllerCrouchSettings,
+    crouched: bool,
+    headroom_blocked: bool,
+    slide: ControllerSlideSettings,
+    slide_cooldown: f32,
+    wall: Option<ControllerWallSettings>,
+    dash: Option<ControllerDashSettings>,
+    air_jump: Option<ControllerAirJumpSettings>,
+    ledge: ControllerLedgeSettings,
+    swim: ControllerSwimSettings,
+    eye_height: f32,
     mass: f32,
+    gravity: Option<Vec3>,
+    current_gravity: Vec3,
+    gravity_scale: f32,
+    max_fall_speed: f32,
+    basis: MovementBasis,
+    turn_speed: Option<f32>,
+    stick: StickResponse,
+    run_threshold: f32,
+}
+
+impl Default for MovementController {
+    fn default() -> Self {
+        Self {
+            speed: ControllerSpeedSettings::default(),
+            acceleration: ControllerAccelerationSettings::default(),
+            forces: ControllerForces::default(),
+            jump_force: 8.0,
+            jump: ControllerJumpSettings::default(),
+            jump_timers: ControllerJumpTimers::default(),
+            grounded: false,
+            ground: ControllerGroundSettings::default(),
+            height: 2.0,
+            radius: 1.0,
+            crouch: ControllerCrouchSettings::default(),
+            crouched: false,
+            headroom_blocked: false,
+            slide: ControllerSlideSettings::default(),
+            slide_cooldown: 0.0,
+            wall: None,
+            dash: None,
+            air_jump: None,
+            ledge: ControllerLedgeSettings::default(),
+            swim: ControllerSwimSettings::default(),
+            eye_height: 3.5,
+            mass: 30.0,
+            gravity: None,
+            current_gravity: Vec3::new(0.0, -9.81, 0.0),
+            gravity_scale: 1.0,
+            max_fall_speed: 50.0,
+            basis: MovementBasis::default(),
+            turn_speed: None,
+            stick: StickResponse::default(),
+            run_threshold: 0.9,
+        }
+    }
 }
 
 impl MovementController {
+    pub fn builder() -> MovementControllerBuilder {
+        MovementControllerBuilder::default()
+    }
+    /// Standing on walkable ground, see [`ControllerGround`] for details
     pub fn grounded(&self) -> bool {
         self.grounded
     }
     pub fn set_grounded(&mut self, grounded: bool) {
         self.grounded = grounded;
     }
+    pub fn ground(&self) -> &ControllerGroundSettings {
+        &self.ground
+    }
+    pub fn set_ground(&mut self, ground: ControllerGroundSettings) {
+        self.ground = ground;
+    }
+    /// Upward velocity given by a jump
     pub fn jump_force(&self) -> f32 {
         self.jump_force
     }
+    pub fn jump(&self) -> &ControllerJumpSettings {
+        &self.jump
+    }
+    pub fn set_jump(&mut self, jump: ControllerJumpSettings) {
+        self.jump = jump;
+    }
+    pub fn jump_timers(&self) -> &ControllerJumpTimers {
+        &self.jump_timers
+    }
+    /// Jump was pressed recently enough to still count
+    pub fn jump_buffered(&self) -> bool {
+        self.jump_timers.since_pressed <= self.jump.buffer_time
+    }
+    /// Jump was pressed recently enough and the ground was left recently enough
+    pub fn can_jump(&self) -> bool {
+        self.jump_buffered() && self.jump_timers.since_grounded <= self.jump.coyote_time
+    }
+    /// Launches the character upwards and consumes the buffered input
+    pub fn start_jump(&mut self) {
+        self.forces.set_gravity(self.up() * self.jump_force);
+        self.jump_timers.since_pressed = f32::INFINITY;
+        self.jump_timers.since_grounded = f32::INFINITY;
+        self.jump_timers.held = true;
+    }
+    /// Jumps off a wall, pushing away along its `normal`
+    pub fn start_wall_jump(&mut self, normal: Vec3) {
+        self.start_jump();
+        let push = self.wall.map_or(0.0, |wall| wall.jump_push);
+        self.forces
+            .set_movement(self.forces.movement() + normal * push);
+    }
+    /// Stops falling when latching onto a wall
+    pub fn start_wall_run(&mut self) {
+        let up = self.up();
+        let gravity = self.forces.gravity();
+        self.forces
+            .set_gravity(gravity - up * gravity.dot(up).min(0.0));
+    }
+    /// Wall running settings, `None` disables wall states
+    pub fn wall(&self) -> Option<&ControllerWallSettings> {
+        self.wall.as_ref()
+    }
+    pub fn set_wall(&mut self, wall: Option<ControllerWallSettings>) {
+        self.wall = wall;
+    }
+    /// Dash settings, `None` disables [`ControllerState::Dash`]
+    pub fn dash(&self) -> Option<&ControllerDashSettings> {
+        self.dash.as_ref()
+    }
+    pub fn set_dash(&mut self, dash: Option<ControllerDashSettings>) {
+        self.dash = dash;
+    }
+    /// Launches the character along `direction` at the dash speed, dropping its vertical velocity
+    pub fn start_dash(&mut self, direction: Vec3) {
+        let speed = self.dash.map_or(0.0, |dash| dash.speed);
+        self.forces.set_movement(direction * speed);
+        self.forces.set_gravity(Vec3::ZERO);
+    }
+    /// Slows the character down to the dash exit speed
+    pub fn end_dash(&mut self) {
+        let exit_speed = self.dash.map_or(0.0, |dash| dash.exit_speed);
+        let movement = self.forces.movement();
+        self.forces
+            .set_movement(movement.clamp_length_max(exit_speed));
+    }
+    /// Air jump settings, `None` disables [`ControllerState::AirJump`]
+    pub fn air_jump(&self) -> Option<&ControllerAirJumpSettings> {
+        self.air_jump.as_ref()
+    }
+    pub fn set_air_jump(&mut self, air_jump: Option<ControllerAirJumpSettings>) {
+        self.air_jump = air_jump;
+    }
+    /// Jumps again in mid-air, replacing the current vertical velocity
+    pub fn start_air_jump(&mut self) {
+        self.start_jump();
+        let force = self
+            .air_jump
+            .map_or(self.jump_force, |air_jump| air_jump.force);
+        self.forces.set_gravity(self.up() * force);
+    }
+    pub fn ledge(&self) -> &ControllerLedgeSettings {
+        &self.ledge
+    }
+    pub fn set_ledge(&mut self, ledge: ControllerLedgeSettings) {
+        self.ledge = ledge;
+    }
+    pub fn swim(&self) -> &ControllerSwimSettings {
+        &self.swim
+    }
+    pub fn set_swim(&mut self, swim: ControllerSwimSettings) {
+        self.swim = swim;
+    }
+    /// Length of the capsule segment while standing, the full height adds `2 * radius`
     pub fn height(&self) -> f32 {
         self.height
     }
+    pub fn crouch(&self) -> &ControllerCrouchSettings {
+        &self.crouch
+    }
+    pub fn set_crouch(&mut self, crouch: ControllerCrouchSettings) {
+        self.crouch = crouch;
+    }
+    /// Capsule is currently shrunk to the crouch height
+    pub fn crouched(&self) -> bool {
+        self.crouched
+    }
+    /// Crouched capsule has no room to stand up, see [`update_headroom_probe`]
+    pub fn headroom_blocked(&self) -> bool {
+        self.headroom_blocked
+    }
+    /// Length of the capsule segment currently in use
+    pub fn current_height(&self) -> f32 {
+        if self.crouched {
+            self.crouch.height
+        } else {
+            self.height
+        }
+    }
+    /// Smoothed eye height above the feet
+    pub fn eye_height(&self) -> f32 {
+        self.eye_height
+    }
+    /// Eye height the smoothing moves towards, half a radius below the top of the capsule
+    pub fn target_eye_height(&self) -> f32 {
+        self.current_height() + self.radius * 1.5
+    }
+    pub fn slide(&self) -> &ControllerSlideSettings {
+        &self.slide
+    }
+    pub fn set_slide(&mut self, slide: ControllerSlideSettings) {
+        self.slide = slide;
+    }
+    /// Seconds left before another slide can start
+    pub fn slide_cooldown(&self) -> f32 {
+        self.slide_cooldown
+    }
+    /// Cooldown is over and the character moves fast enough to keep a slide going
+    pub fn can_slide(&self) -> bool {
+        self.slide_cooldown <= 0.0 && self.forces.movement().length() >= self.slide.min_speed
+    }
+    /// Boosts the movement velocity along its current direction
+    pub fn start_slide(&mut self) {
+        let movement = self.forces.movement();
+        let boost = movement.normalize_or_zero() * self.slide.boost;
+        self.forces.set_movement(movement + boost);
+    }
+    /// Starts the cooldown before the next slide
+    pub fn end_slide(&mut self) {
+        self.slide_cooldown = self.slide.cooldown;
+    }
+    pub(crate) fn set_slide_cooldown(&mut self, slide_cooldown: f32) {
+        self.slide_cooldown = slide_cooldown;
+    }
+    pub(crate) fn set_crouched(&mut self, crouched: bool) {
+        self.crouched = crouched;
+    }
+    pub(crate) fn set_headroom_blocked(&mut self, headroom_blocked: bool) {
+        self.headroom_blocked = headroom_blocked;
+    }
+    pub(crate) fn set_eye_height(&mut self, eye_height: f32) {
+        self.eye_height = eye_height;
+    }
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+    /// No longer affects movement, gravity and jumps are independent of mass
+    #[deprecated(note = "gravity is an acceleration and ignores mass")]
     pub fn mass(&self) -> f32 {
         self.mass
     }
@@ -60,12 +433,80 @@ impl MovementController {
     pub fn set_jump_force(&mut self, jump_force: f32) {
         self.jump_force = jump_force;
     }
+    pub(crate) fn jump_timers_mut(&mut self) -> &mut ControllerJumpTimers {
+        &mut self.jump_timers
+    }
     pub fn set_height(&mut self, height: f32) {
         self.height = height;
     }
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius;
+    }
+    /// No longer affects movement, gravity and jumps are independent of mass
+    #[deprecated(note = "gravity is an acceleration and ignores mass")]
     pub fn set_mass(&mut self, mass: f32) {
         self.mass = mass;
     }
+    /// Gravity override, `None` follows [`RapierConfiguration::gravity`]
+    pub fn gravity(&self) -> Option<Vec3> {
+        self.gravity
+    }
+    pub fn set_gravity(&mut self, gravity: Option<Vec3>) {
+        self.gravity = gravity;
+    }
+    /// Gravity acceleration applied during the last update
+    pub fn current_gravity(&self) -> Vec3 {
+        self.current_gravity
+    }
+    /// Direction opposite to gravity
+    pub fn up(&self) -> Vec3 {
+        (-self.current_gravity).try_normalize().unwrap_or(Vec3::Y)
+    }
+    /// Speed along gravity past which falling no longer accelerates
+    pub fn max_fall_speed(&self) -> f32 {
+        self.max_fall_speed
+    }
+    pub fn set_max_fall_speed(&mut self, max_fall_speed: f32) {
+        self.max_fall_speed = max_fall_speed;
+    }
+    pub fn basis(&self) -> MovementBasis {
+        self.basis
+    }
+    pub fn set_basis(&mut self, basis: MovementBasis) {
+        self.basis = basis;
+    }
+    /// Rate at which the character turns to face its movement direction,
+    /// `None` keeps the rotation untouched. Meant for [`MovementBasis::Camera`],
+    /// with [`MovementBasis::Character`] the input would turn along with the character
+    pub fn turn_speed(&self) -> Option<f32> {
+        self.turn_speed
+    }
+    pub fn set_turn_speed(&mut self, turn_speed: Option<f32>) {
+        self.turn_speed = turn_speed;
+    }
+    pub fn stick(&self) -> &StickResponse {
+        &self.stick
+    }
+    pub fn set_stick(&mut self, stick: StickResponse) {
+        self.stick = stick;
+    }
+    /// Stick tilt at which the character starts running without sprint
+    pub fn run_threshold(&self) -> f32 {
+        self.run_threshold
+    }
+    pub fn set_run_threshold(&mut self, run_threshold: f32) {
+        self.run_threshold = run_threshold;
+    }
+    pub fn acceleration(&self) -> &ControllerAccelerationSettings {
+        &self.acceleration
+    }
+    pub fn set_acceleration(&mut self, acceleration: ControllerAccelerationSettings) {
+        self.acceleration = acceleration;
+    }
+    /// Current velocity of the character, momentum included
+    pub fn velocity(&self) -> Vec3 {
+        self.forces.movement() + self.forces.actions() + self.forces.gravity()
+    }
     pub fn forces(&self) -> &ControllerForces {
         &self.forces
     }
@@ -74,130 +515,203 @@ impl MovementController {
     }
 }
 
-fn spawn_player(mut commands: Commands) {
-    let settings = MovementController {
-        speed: ControllerSpeedSettings {
-            base: ControllerSpeed(10.),
-            run: ControllerSpeed(20.),
-            crouch: ControllerSpeed(5.),
-            slide: ControllerSpeed(25.),
-        },
-        forces: Default::default(),
-        jump_force: 30.0,
-        height: 2.0,
-        mass: 30.0,
-        grounded: false,
-    };
+fn spawn_player(
+    commands: &mut Commands,
+    movement: &InputBindings<CharacterMovement>,
+    actions: &InputBindings<CharacterActions>,
+) -> Entity {
     commands
-        .spawn(RigidBody::KinematicPositionBased)
-        .insert(KinematicCharacterController {
-            offset: CharacterLength::Absolute(0.05),
-            slide: false,
-            autostep: Some(CharacterAutostep {
-                max_height: CharacterLength::Absolute(0.5),
-                min_width: CharacterLength::Absolute(0.2),
-                include_dynamic_bodies: false,
-            }),
-            max_slope_climb_angle: 45.0_f32.to_radians(),
-            min_slope_slide_angle: 30.0_f32.to_radians(),
-            apply_impulse_to_dynamic_bodies: true,
-            snap_to_ground: Some(CharacterLength::Absolute(0.1)),
-            ..Default::default()
-        })
-        .insert(Collider::capsule_y(settings.height / 2., 1.0))
-        .insert(Restitution::coefficient(1.0))
-        .insert(TransformBundle::from(Transform::from_xyz(0.0, 4.0, 0.0)))
-        .insert(settings)
-        .insert(InputManagerBundle::<CharacterMovement> {
-            input_map: InputMap::default()
-                .insert(KeyCode::W, CharacterMovement::Forward)
-                .insert(KeyCode::A, CharacterMovement::Left)
-                .insert(KeyCode::S, CharacterMovement::Back)
-                .insert(KeyCode::D, CharacterMovement::Right)
-                .build(),
-            action_state: ActionState::default(),
-        })
-        .insert(InputManagerBundle::<CharacterActions> {
-            input_map: InputMap::default()
-                .insert(KeyCode::Space, CharacterActions::Jump)
-                .insert(KeyCode::ControlLeft, CharacterActions::Crouch)
-                .insert(KeyCode::ShiftLeft, CharacterActions::Sprint)
+        .spawn(
+            MovementController::builder()
+                .with_transform(Transform::from_xyz(0.0, 4.0, 0.0))
+                .with_input_maps(movement.0.clone(), actions.0.clone())
                 .build(),
-            action_state: ActionState::default(),
-        })
-        .insert(KinematicCharacterControllerOutput::default())
-        .insert(CameraTarget {});
+        )
+        .insert(CameraTarget::default())
+        .id()
 }
 
 fn update_gravity_force(
-    mut q: Query<(&mut MovementController, &KinematicCharacterControllerOutput)>,
+    mut q: Query<(&mut MovementController, &ControllerState, &ControllerFluid)>,
+    rapier: Option<Res<RapierConfiguration>>,
     time: Res<Time>,
 ) {
-    let (mut character, physics) = q.single_mut();
+    let world_gravity = rapier.map_or(Vec3::new(0.0, -9.81, 0.0), |config| config.gravity);
 
-    let mass = character.mass;
-    let gravity_constant = Vec3::new(0.0, -9.81, 0.0);
-    let gravity_force = character.forces.gravity();
+    for (mut character, state, fluid) in q.iter_mut() {
+        let gravity = character.gravity.unwrap_or(world_gravity);
+        let scale = match (state, character.wall) {
+            (ControllerState::WallRun, Some(wall)) => wall.gravity_scale,
+            (
+                ControllerState::Hang
+                | ControllerState::Mantle
+                | ControllerState::Climb
+                | ControllerState::Dash,
+                _,
+            ) => 0.0,
+            (ControllerState::Swim | ControllerState::Dive, _) => fluid.gravity_scale(),
+            _ => 1.0,
+        };
+        let down = gravity.normalize_or_zero();
+        let mut velocity = character.forces.gravity();
 
-    if physics.grounded {
-        character.forces.set_gravity(Vec3::ZERO);
-    } else {
-        character
-            .forces
-            .set_gravity(gravity_force + (gravity_constant * mass * time.delta_seconds()));
-    };
+        // Resting on the ground still pulls down by one step to keep the contact
+        if character.grounded && velocity.dot(down) >= 0.0 || scale == 0.0 {
+            velocity = Vec3::ZERO;
+        }
+
+        velocity += gravity * scale * time.delta_seconds();
+
+        if let (ControllerState::Swim | ControllerState::Dive, Some(volume)) =
+            (state, fluid.volume())
+        {
+            velocity *= (-volume.drag * time.delta_seconds()).exp();
+        }
+
+        let fall_speed = velocity.dot(down);
+        if fall_speed > character.max_fall_speed {
+            velocity -= down * (fall_speed - character.max_fall_speed);
+        }
+
+        character.current_gravity = gravity;
+        character.gravity_scale = scale;
+        character.forces.set_gravity(velocity);
+    }
 }
 
 fn update_movement_force(
-    mut q: Query<(&mut MovementController, &ActionState<CharacterMovement>)>,
-
-    speed: ResMut<ControllerSpeed>,
+    mut q: Query<(
+        &mut MovementController,
+        &ControllerSpeed,
+        &ActionState<CharacterMovement>,
+        &Transform,
+        &ControllerGround,
+        &ControllerFluid,
+        &ControllerState,
+    )>,
+    cameras: Query<(&Transform, &bevy::render::camera::Camera), With<crate::camera::Camera>>,
+    time: Res<Time>,
 ) {
-    let (mut character, movement) = q.single_mut();
-    let speed = speed;
-
-    let movement = movement
-        .get_pressed()
+    let camera = cameras
         .iter()
-        .map(|movement| movement.into_vec())
-        .sum::<Vec3>()
-        .mul(speed.get() as f32)
-        .clamp_length(0., speed.get());
+        .find(|(_, camera)| camera.is_active)
+        .map(|(transform, _)| transform);
+    let camera_yaw = camera.map(yaw);
+    let camera_pitch = camera.map_or(Quat::IDENTITY, pitch);
+
+    for (mut character, speed, movement, transform, ground, fluid, state) in q.iter_mut() {
+        match state {
+            // Sliding and climbing set their own velocity, see `update_slide_force`
+            // and `update_climb_force`, a dash keeps the one it started with
+            ControllerState::Slide | ControllerState::Climb | ControllerState::Dash => continue,
+            ControllerState::Hang | ControllerState::Mantle => {
+                character.forces.set_movement(Vec3::ZERO);
+                continue;
+            }
+            _ => {}
+        }
 
-    character.forces.set_movement(movement);
+        let rotation = match character.basis {
+            MovementBasis::Character => transform.rotation,
+            MovementBasis::Camera => camera_yaw.unwrap_or(transform.rotation),
+        };
+
+        let input = CharacterMovement::input(movement, &character.stick);
+        let mut target = rotation * input.mul(speed.get());
+        let mut velocity = character.forces.movement();
+        let settings = character.acceleration;
+        let up = character.up();
+
+        let swimming = matches!(state, ControllerState::Swim | ControllerState::Dive);
+        if let (true, Some(volume)) = (swimming, fluid.volume()) {
+            // Swim where the camera looks, but only dive down from the surface
+            target = rotation * camera_pitch * input.mul(speed.get());
+            if *state == ControllerState::Swim {
+                target -= up * target.dot(up).max(0.0);
+            }
+
+            let velocity = if input != Vec3::ZERO {
+                let rate = character.swim.acceleration * time.delta_seconds();
+                move_towards(velocity, target, rate)
+            } else {
+                velocity * (-volume.drag * time.delta_seconds()).exp()
+            };
+            character.forces.set_movement(velocity);
+            continue;
+        }
+
+        if ground.grounded() {
+            // Follow the slope instead of walking off it or into it
+            target = project_on_ground(target, ground.normal(), up);
+            velocity = project_on_ground(velocity, ground.normal(), up);
+        } else {
+            // Vertical momentum left from a slope carries on as gravity velocity
+            let vertical = up * velocity.dot(up);
+            velocity -= vertical;
+            let gravity = character.forces.gravity();
+            character.forces.set_gravity(gravity + vertical);
+        }
+
+        let rate = match (ground.grounded(), input != Vec3::ZERO) {
+            (true, true) => settings.ground_acceleration * ground.friction(),
+            (true, false) => settings.ground_friction * ground.friction(),
+            (false, true) => settings.air_acceleration * settings.air_control,
+            (false, false) => 0.0,
+        };
+
+        let velocity = move_towards(velocity, target, rate * time.delta_seconds());
+        character.forces.set_movement(velocity);
+    }
 }
 
-fn update_player_speed(
-    q: Query<&MovementController>,
-    state: Res<State<ControllerState>>,
-    mut speed: ResMut<ControllerSpeed>,
-) {
-    let character = q.single();
+pub(crate) fn move_towards(from: Vec3, to: Vec3, max_delta: f32) -> Vec3 {
+    let delta = to - from;
+    if delta.length() <= max_delta {
+        to
+    } else {
+        from + delta.normalize() * max_delta
+    }
+}
 
-    let new_speed: Option<ControllerSpeed> = match state.get() {
-        ControllerState::Run => Some(character.speed.run),
-        ControllerState::Walk => Some(character.speed.base),
-        ControllerState::Slide => Some(character.speed.slide),
-        ControllerState::Crouch => Some(character.speed.crouch),
-        _ => None,
-    };
+/// Rotation around the local X axis matching the vertical facing of `transform`
+fn pitch(transform: &Transform) -> Quat {
+    Quat::from_rotation_x(transform.forward().y.clamp(-1.0, 1.0).asin())
+}
 
-    if let Some(new_speed) = new_speed {
-        *speed = new_speed
+/// Rotation around the global Y axis matching the horizontal facing of `transform`
+pub(crate) fn yaw(transform: &Transform) -> Quat {
+    let mut forward = transform.forward();
+    if forward.x.abs() < f32::EPSILON && forward.z.abs() < f32::EPSILON {
+        // Looking straight up or down, the up vector points where forward would be
+        forward = transform.up() * -forward.y.signum();
     }
+
+    Quat::from_rotation_y(f32::atan2(-forward.x, -forward.z))
 }
 
-fn update_action_force(mut q: Query<&mut MovementController>, state: Res<State<ControllerState>>) {
-    let mut character = q.single_mut();
-    let move_direction = character.forces.movement();
+fn update_player_speed(
+    mut q: Query<(&MovementController, &ControllerState, &mut ControllerSpeed)>,
+) {
+    for (character, state, mut speed) in q.iter_mut() {
+        let new_speed: Option<ControllerSpeed> = match state {
+            ControllerState::Run => Some(character.speed.run),
+            ControllerState::Walk => Some(character.speed.base),
+            ControllerState::Slide => Some(character.speed.slide),
+            ControllerState::Crouch => Some(character.speed.crouch),
+            ControllerState::Swim | ControllerState::Dive => Some(character.speed.swim),
+            _ => None,
+        };
 
-    let action_force = match state.get() {
-        ControllerState::Slide => move_direction,
-        ControllerState::Jump => Vec3::new(0., character.jump_force, 0.),
-        _ => Vec3::ZERO,
-    };
+        if let Some(new_speed) = new_speed {
+            speed.set_if_neq(new_speed);
+        }
+    }
+}
 
-    character.forces.set_actions(action_force);
+fn update_action_force(mut q: Query<&mut MovementController>) {
+    for mut character in q.iter_mut() {
+        character.forces.set_actions(Vec3::ZERO);
+    }
 }
 
 #[rustfmt::skip]
@@ -205,20 +719,41 @@ fn update_player_pos(
     mut q: Query<(
         &mut KinematicCharacterController,
         &MovementController,
-        &Transform
     )>,
     time: Res<Time>,
 ) {
-    let (mut controller,  character, transform) = q.single_mut();
+    for (mut controller, character) in q.iter_mut() {
+        // Average of the velocities before and after this step, exact for constant gravity
+        let acceleration = character.current_gravity * character.gravity_scale;
+        let gravity = character.forces.gravity() - acceleration * time.delta_seconds() * 0.5;
+        let movement = character.forces.movement();
+        let actions = character.forces.actions();
+
+        let direction = movement
+            .add(actions) 
+            .add(gravity)
+            .mul(time.delta_seconds());
 
-    let gravity = character.forces.gravity();
-    let movement = character.forces.movement();
-    let actions = character.forces.actions();
+        controller.translation = Some(direction);
+    }
+}
 
-    let direction = movement
-        .add(actions) 
-        .add(gravity)
-        .mul(time.delta_seconds());
+fn update_player_rot(mut q: Query<(&mut Transform, &MovementController)>, time: Res<Time>) {
+    for (mut transform, character) in q.iter_mut() {
+        let Some(turn_speed) = character.turn_speed else {
+            continue;
+        };
 
-    controller.translation = Some(transform.rotation * direction);
+        // Turn around the controller's up axis, which follows gravity
+        let up = character.up();
+        let movement = character.forces.movement();
+        let direction = movement - up * movement.dot(up);
+        if direction.length_squared() < f32::EPSILON {
+            continue;
+        }
+
+        let target = Transform::IDENTITY.looking_to(direction, up).rotation;
+        let t = 1.0 - (-turn_speed * time.delta_seconds()).exp();
+        transform.rotation = transform.rotation.slerp(target, t);
+    }
 }
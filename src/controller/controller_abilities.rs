@@ -0,0 +1,184 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Movement ability with [`AbilityChargeSettings`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ability {
+    Dash,
+    AirJump,
+}
+
+/// Sent when a character starts a [`ControllerState::Dash`]
+#[derive(Event, Debug, Clone)]
+pub struct Dashed {
+    pub entity: Entity,
+    pub direction: Vec3,
+}
+
+/// Sent when a character jumps in mid-air
+#[derive(Event, Debug, Clone)]
+pub struct AirJumped {
+    pub entity: Entity,
+    /// Air jumps left after this one
+    pub remaining: u32,
+}
+
+/// Sent when an ability regains charges, either over time or by landing
+#[derive(Event, Debug, Clone)]
+pub struct AbilityRecharged {
+    pub entity: Entity,
+    pub ability: Ability,
+    pub charges: u32,
+}
+
+/// Runtime charges of a single ability
+#[derive(Default, Debug, Clone, Copy)]
+pub struct AbilityCharges {
+    used: u32,
+    cooldown: f32,
+    recharge: f32,
+}
+
+impl AbilityCharges {
+    /// Charges left out of `settings.charges`
+    pub fn available(&self, settings: &AbilityChargeSettings) -> u32 {
+        settings.charges.saturating_sub(self.used)
+    }
+    /// Seconds left before the ability can be used again
+    pub fn cooldown(&self) -> f32 {
+        self.cooldown
+    }
+    /// A charge is left and the cooldown is over
+    pub fn ready(&self, settings: &AbilityChargeSettings) -> bool {
+        self.available(settings) > 0 && self.cooldown <= 0.0
+    }
+    fn consume(&mut self, settings: &AbilityChargeSettings) {
+        self.used += 1;
+        self.cooldown = settings.cooldown;
+    }
+    /// Advances the cooldown and recharge, returns whether charges came back
+    fn tick(&mut self, settings: &AbilityChargeSettings, grounded: bool, delta: f32) -> bool {
+        let used = self.used;
+        self.cooldown = (self.cooldown - delta).max(0.0);
+
+        if grounded && settings.refill_on_land {
+            self.used = 0;
+        } else if self.used > 0 {
+            self.recharge += delta;
+            if self.recharge >= settings.recharge {
+                self.recharge = 0.0;
+                self.used -= 1;
+            }
+        }
+
+        if self.used == 0 {
+            self.recharge = 0.0;
+        }
+
+        self.used < used
+    }
+}
+
+/// Charges of the abilities enabled on the [`MovementController`] and the current dash
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct ControllerAbilities {
+    dash: AbilityCharges,
+    air_jump: AbilityCharges,
+    dash_aim: Vec3,
+    dash_direction: Vec3,
+    dash_time: f32,
+}
+
+impl ControllerAbilities {
+    pub fn dash(&self) -> &AbilityCharges {
+        &self.dash
+    }
+    pub fn air_jump(&self) -> &AbilityCharges {
+        &self.air_jump
+    }
+    /// Direction of the current or last dash
+    pub fn dash_direction(&self) -> Vec3 {
+        self.dash_direction
+    }
+    /// Seconds since the current or last dash started
+    pub fn dash_time(&self) -> f32 {
+        self.dash_time
+    }
+    /// Uses a dash charge and aims the dash along the current movement input
+    pub(crate) fn start_dash(&mut self, settings: &AbilityChargeSettings) -> Vec3 {
+        self.dash.consume(settings);
+        self.dash_direction = self.dash_aim;
+        self.dash_time = 0.0;
+        self.dash_direction
+    }
+    pub(crate) fn start_air_jump(&mut self, settings: &AbilityChargeSettings) {
+        self.air_jump.consume(settings);
+    }
+}
+
+/// Recharges abilities and aims dashes, the [`ControllerStateMachine`] starts them
+/// through [`ControllerState::Dash`] and [`ControllerState::AirJump`]
+pub fn update_player_abilities(
+    mut q: Query<(
+        Entity,
+        &MovementController,
+        &mut ControllerAbilities,
+        &ControllerState,
+        &ActionState<CharacterMovement>,
+        &Transform,
+    )>,
+    cameras: Query<(&Transform, &bevy::render::camera::Camera), With<crate::camera::Camera>>,
+    mut recharged: EventWriter<AbilityRecharged>,
+    time: Res<Time>,
+) {
+    let camera_yaw = cameras
+        .iter()
+        .find(|(_, camera)| camera.is_active)
+        .map(|(transform, _)| yaw(transform));
+
+    for (entity, character, mut abilities, state, movement, transform) in q.iter_mut() {
+        let grounded = character.grounded();
+        let delta = time.delta_seconds();
+        let abilities = &mut *abilities;
+
+        let charges = [
+            (
+                Ability::Dash,
+                &mut abilities.dash,
+                character.dash().map(|dash| dash.charges),
+            ),
+            (
+                Ability::AirJump,
+                &mut abilities.air_jump,
+                character.air_jump().map(|air_jump| air_jump.charges),
+            ),
+        ];
+        for (ability, charges, settings) in charges {
+            let Some(settings) = settings else {
+                continue;
+            };
+            if charges.tick(&settings, grounded, delta) {
+                recharged.send(AbilityRecharged {
+                    entity,
+                    ability,
+                    charges: charges.available(&settings),
+                });
+            }
+        }
+
+        if *state == ControllerState::Dash {
+            abilities.dash_time += delta;
+        }
+
+        let rotation = match character.basis() {
+            MovementBasis::Character => transform.rotation,
+            MovementBasis::Camera => camera_yaw.unwrap_or(transform.rotation),
+        };
+        let up = character.up();
+        let input = rotation * CharacterMovement::input(movement, character.stick());
+        abilities.dash_aim = (input - up * input.dot(up))
+            .try_normalize()
+            .unwrap_or(transform.forward());
+    }
+}
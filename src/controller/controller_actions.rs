@@ -1,9 +1,27 @@
+use crate::prelude::*;
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Actionlike, Reflect, Clone, Hash, Debug, Copy, PartialEq, Eq)]
+#[derive(Actionlike, Reflect, Clone, Hash, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CharacterActions {
     Jump,
     Sprint,
     Crouch,
-}
\ No newline at end of file
+    Dash,
+}
+
+impl DefaultBindings for CharacterActions {
+    fn default_input_map() -> InputMap<Self> {
+        InputMap::default()
+            .insert(KeyCode::Space, CharacterActions::Jump)
+            .insert(KeyCode::ControlLeft, CharacterActions::Crouch)
+            .insert(KeyCode::ShiftLeft, CharacterActions::Sprint)
+            .insert(KeyCode::Q, CharacterActions::Dash)
+            .insert(GamepadButtonType::South, CharacterActions::Jump)
+            .insert(GamepadButtonType::East, CharacterActions::Crouch)
+            .insert(GamepadButtonType::LeftThumb, CharacterActions::Sprint)
+            .insert(GamepadButtonType::West, CharacterActions::Dash)
+            .build()
+    }
+}
@@ -0,0 +1,194 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::time::Duration;
+
+/// Values for driving a skeletal animation of the character, refreshed every frame
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct CharacterAnimationParams {
+    state: ControllerState,
+    speed: f32,
+    vertical_velocity: f32,
+    grounded: bool,
+    lean: f32,
+    turn_rate: f32,
+    forward: Option<Vec3>,
+}
+
+impl CharacterAnimationParams {
+    pub fn state(&self) -> ControllerState {
+        self.state
+    }
+    /// Speed along the ground divided by [`ControllerSpeedSettings::run`], `1.0` at a full run
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+    /// Velocity along up, positive while rising
+    pub fn vertical_velocity(&self) -> f32 {
+        self.vertical_velocity
+    }
+    pub fn grounded(&self) -> bool {
+        self.grounded
+    }
+    /// Sideways tilt into turns in radians, positive when leaning right
+    pub fn lean(&self) -> f32 {
+        self.lean
+    }
+    /// Turning speed around up in radians per second, positive when turning left
+    pub fn turn_rate(&self) -> f32 {
+        self.turn_rate
+    }
+}
+
+/// Largest lean into a turn, in radians
+const MAX_LEAN: f32 = 0.5;
+/// Rate at which the lean follows turns
+const LEAN_SPEED: f32 = 8.0;
+
+/// Fills [`CharacterAnimationParams`] from the controller and the rendered transform
+pub fn update_animation_params(
+    mut q: Query<(
+        &MovementController,
+        &ControllerState,
+        &Transform,
+        &mut CharacterAnimationParams,
+    )>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+    if delta <= 0.0 {
+        return;
+    }
+
+    for (character, state, transform, mut params) in q.iter_mut() {
+        let up = character.up();
+        let velocity = character.velocity();
+        let planar = (velocity - up * velocity.dot(up)).length();
+        let run = character.speed().run.get();
+
+        let forward = transform.forward();
+        let forward = (forward - up * forward.dot(up)).try_normalize();
+        let turn_rate = match (params.forward, forward) {
+            (Some(previous), Some(current)) => {
+                let angle = previous.angle_between(current);
+                angle * previous.cross(current).dot(up).signum() / delta
+            }
+            _ => 0.0,
+        };
+
+        // Centripetal acceleration against gravity, like a runner leaning into a curve.
+        // Without gravity, e.g. while dashing or climbing, there is nothing to lean against.
+        let gravity = character.current_gravity().length();
+        let target = if gravity > f32::EPSILON {
+            (-(planar * turn_rate) / gravity)
+                .atan()
+                .clamp(-MAX_LEAN, MAX_LEAN)
+        } else {
+            0.0
+        };
+        let t = 1.0 - (-LEAN_SPEED * delta).exp();
+
+        params.state = *state;
+        params.speed = if run > 0.0 { planar / run } else { 0.0 };
+        params.vertical_velocity = velocity.dot(up);
+        params.grounded = character.grounded();
+        params.lean += (target - params.lean) * t;
+        params.turn_rate = turn_rate;
+        params.forward = forward;
+    }
+}
+
+/// Plays an [`AnimationClip`] per [`ControllerState`] on the [`AnimationPlayer`] found
+/// among the character's descendants, usually inside a glTF scene spawned as a child
+#[derive(Component, Debug, Clone)]
+pub struct CharacterAnimations {
+    clips: HashMap<ControllerState, (Handle<AnimationClip>, bool)>,
+    /// Seconds spent blending from one clip into the next
+    pub crossfade: f32,
+    playing: Option<Handle<AnimationClip>>,
+    state: Option<ControllerState>,
+}
+
+impl Default for CharacterAnimations {
+    fn default() -> Self {
+        CharacterAnimations {
+            clips: HashMap::default(),
+            crossfade: 0.2,
+            playing: None,
+            state: None,
+        }
+    }
+}
+
+impl CharacterAnimations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Loops `clip` while in `state`
+    pub fn with_clip(mut self, state: ControllerState, clip: Handle<AnimationClip>) -> Self {
+        self.clips.insert(state, (clip, true));
+        self
+    }
+    /// Plays `clip` once when entering `state`, holding its last frame
+    pub fn with_clip_once(mut self, state: ControllerState, clip: Handle<AnimationClip>) -> Self {
+        self.clips.insert(state, (clip, false));
+        self
+    }
+    pub fn with_crossfade(mut self, crossfade: f32) -> Self {
+        self.crossfade = crossfade;
+        self
+    }
+    /// Clip mapped to `state`, states without one keep the current clip
+    pub fn clip(&self, state: ControllerState) -> Option<&Handle<AnimationClip>> {
+        self.clips.get(&state).map(|(clip, _)| clip)
+    }
+    /// Clip currently playing
+    pub fn playing(&self) -> Option<&Handle<AnimationClip>> {
+        self.playing.as_ref()
+    }
+}
+
+/// Crossfades to the clip of the current state whenever it changes, and restarts
+/// clips played once every time their state is entered
+pub fn update_character_animations(
+    mut q: Query<(Entity, &CharacterAnimationParams, &mut CharacterAnimations)>,
+    children: Query<&Children>,
+    mut players: Query<&mut AnimationPlayer>,
+) {
+    for (entity, params, mut animations) in q.iter_mut() {
+        let entered = animations.state != Some(params.state);
+        let Some((clip, repeat)) = animations.clips.get(&params.state).cloned() else {
+            animations.state = Some(params.state);
+            continue;
+        };
+        let same = animations.playing.as_ref() == Some(&clip);
+        if same && (repeat || !entered) {
+            animations.state = Some(params.state);
+            continue;
+        }
+
+        let Some(player) = children
+            .iter_descendants(entity)
+            .find(|child| players.contains(*child))
+        else {
+            continue;
+        };
+        let Ok(mut player) = players.get_mut(player) else {
+            continue;
+        };
+
+        if same {
+            player.replay();
+        } else if animations.playing.is_some() {
+            let crossfade = Duration::from_secs_f32(animations.crossfade.max(0.0));
+            player.play_with_transition(clip.clone(), crossfade);
+        } else {
+            player.play(clip.clone());
+        }
+        if repeat {
+            player.repeat();
+        }
+        animations.playing = Some(clip);
+        animations.state = Some(params.state);
+    }
+}
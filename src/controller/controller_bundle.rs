@@ -0,0 +1,223 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Everything needed to spawn a character driven by [`MovementController`]
+#[derive(Bundle)]
+pub struct MovementControllerBundle {
+    pub controller: MovementController,
+    pub state: ControllerState,
+    pub state_history: ControllerStateHistory,
+    pub speed: ControllerSpeed,
+    pub rigid_body: RigidBody,
+    pub character_controller: KinematicCharacterController,
+    pub character_output: KinematicCharacterControllerOutput,
+    pub collider: Collider,
+    pub restitution: Restitution,
+    pub transform: TransformBundle,
+    pub movement_input: InputManagerBundle<CharacterMovement>,
+    pub actions_input: InputManagerBundle<CharacterActions>,
+    pub input: ControllerInput,
+    pub ground: ControllerGround,
+    pub platform: ControllerPlatform,
+    pub wall: ControllerWall,
+    pub ledge: ControllerLedge,
+    pub climb: ControllerClimb,
+    pub fluid: ControllerFluid,
+    pub abilities: ControllerAbilities,
+    pub animation: CharacterAnimationParams,
+    pub interpolation: RenderInterpolation,
+}
+
+impl Default for MovementControllerBundle {
+    fn default() -> Self {
+        MovementControllerBuilder::default().build()
+    }
+}
+
+pub struct MovementControllerBuilder {
+    controller: MovementController,
+    max_slope_climb_angle: f32,
+    min_slope_slide_angle: f32,
+    step_height: f32,
+    transform: Transform,
+    movement_input: InputMap<CharacterMovement>,
+    actions_input: InputMap<CharacterActions>,
+}
+
+impl Default for MovementControllerBuilder {
+    fn default() -> Self {
+        Self {
+            controller: MovementController::default(),
+            max_slope_climb_angle: 45.0_f32.to_radians(),
+            min_slope_slide_angle: 30.0_f32.to_radians(),
+            step_height: 0.5,
+            transform: Transform::default(),
+            movement_input: CharacterMovement::default_input_map(),
+            actions_input: CharacterActions::default_input_map(),
+        }
+    }
+}
+
+impl MovementControllerBuilder {
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.controller.set_height(height);
+        self
+    }
+    pub fn with_crouch(mut self, crouch: ControllerCrouchSettings) -> Self {
+        self.controller.set_crouch(crouch);
+        self
+    }
+    pub fn with_ground(mut self, ground: ControllerGroundSettings) -> Self {
+        self.controller.set_ground(ground);
+        self
+    }
+    pub fn with_ledge(mut self, ledge: ControllerLedgeSettings) -> Self {
+        self.controller.set_ledge(ledge);
+        self
+    }
+    /// Enables [`ControllerState::WallRun`] and [`ControllerState::WallJump`]
+    pub fn with_wall_run(mut self, wall: ControllerWallSettings) -> Self {
+        self.controller.set_wall(Some(wall));
+        self
+    }
+    pub fn with_swim(mut self, swim: ControllerSwimSettings) -> Self {
+        self.controller.set_swim(swim);
+        self
+    }
+    /// Enables [`ControllerState::Dash`]
+    pub fn with_dash(mut self, dash: ControllerDashSettings) -> Self {
+        self.controller.set_dash(Some(dash));
+        self
+    }
+    /// Enables [`ControllerState::AirJump`]
+    pub fn with_air_jumps(mut self, air_jump: ControllerAirJumpSettings) -> Self {
+        self.controller.set_air_jump(Some(air_jump));
+        self
+    }
+    pub fn with_slide(mut self, slide: ControllerSlideSettings) -> Self {
+        self.controller.set_slide(slide);
+        self
+    }
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.controller.set_radius(radius);
+        self
+    }
+    pub fn with_speed(mut self, speed: ControllerSpeedSettings) -> Self {
+        self.controller.set_speed(speed);
+        self
+    }
+    pub fn with_acceleration(mut self, acceleration: ControllerAccelerationSettings) -> Self {
+        self.controller.set_acceleration(acceleration);
+        self
+    }
+    pub fn with_jump_force(mut self, jump_force: f32) -> Self {
+        self.controller.set_jump_force(jump_force);
+        self
+    }
+    pub fn with_jump(mut self, jump: ControllerJumpSettings) -> Self {
+        self.controller.set_jump(jump);
+        self
+    }
+    /// Gravity override, by default the controller follows [`RapierConfiguration::gravity`]
+    pub fn with_gravity(mut self, gravity: Vec3) -> Self {
+        self.controller.set_gravity(Some(gravity));
+        self
+    }
+    pub fn with_max_fall_speed(mut self, max_fall_speed: f32) -> Self {
+        self.controller.set_max_fall_speed(max_fall_speed);
+        self
+    }
+    /// No longer affects movement, see [`MovementController::set_mass`]
+    #[deprecated(note = "gravity is an acceleration and ignores mass")]
+    #[allow(deprecated)]
+    pub fn with_mass(mut self, mass: f32) -> Self {
+        self.controller.set_mass(mass);
+        self
+    }
+    pub fn with_basis(mut self, basis: MovementBasis) -> Self {
+        self.controller.set_basis(basis);
+        self
+    }
+    /// Smoothly turn the character towards its movement direction
+    pub fn with_face_movement(mut self, turn_speed: f32) -> Self {
+        self.controller.set_turn_speed(Some(turn_speed));
+        self
+    }
+    /// Slope limits in radians
+    pub fn with_slope_limits(mut self, max_climb_angle: f32, min_slide_angle: f32) -> Self {
+        self.max_slope_climb_angle = max_climb_angle;
+        self.min_slope_slide_angle = min_slide_angle;
+        self
+    }
+    pub fn with_step_height(mut self, step_height: f32) -> Self {
+        self.step_height = step_height;
+        self
+    }
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_input_maps(
+        mut self,
+        movement: InputMap<CharacterMovement>,
+        actions: InputMap<CharacterActions>,
+    ) -> Self {
+        self.movement_input = movement;
+        self.actions_input = actions;
+        self
+    }
+
+    pub fn build(self) -> MovementControllerBundle {
+        let mut controller = self.controller;
+        controller.set_eye_height(controller.target_eye_height());
+        let autostep = (self.step_height > 0.0).then_some(CharacterAutostep {
+            max_height: CharacterLength::Absolute(self.step_height),
+            min_width: CharacterLength::Absolute(0.2),
+            include_dynamic_bodies: false,
+        });
+
+        MovementControllerBundle {
+            state: ControllerState::default(),
+            state_history: ControllerStateHistory::default(),
+            speed: controller.speed().base,
+            rigid_body: RigidBody::KinematicPositionBased,
+            character_controller: KinematicCharacterController {
+                offset: CharacterLength::Absolute(0.05),
+                slide: false,
+                autostep,
+                max_slope_climb_angle: self.max_slope_climb_angle,
+                min_slope_slide_angle: self.min_slope_slide_angle,
+                apply_impulse_to_dynamic_bodies: true,
+                snap_to_ground: Some(CharacterLength::Absolute(0.1)),
+                filter_flags: QueryFilterFlags::EXCLUDE_SENSORS,
+                ..Default::default()
+            },
+            character_output: KinematicCharacterControllerOutput::default(),
+            collider: Collider::capsule_y(controller.height() / 2., controller.radius()),
+            restitution: Restitution::coefficient(1.0),
+            transform: TransformBundle::from(self.transform),
+            movement_input: InputManagerBundle::<CharacterMovement> {
+                input_map: self.movement_input,
+                action_state: ActionState::default(),
+            },
+            actions_input: InputManagerBundle::<CharacterActions> {
+                input_map: self.actions_input,
+                action_state: ActionState::default(),
+            },
+            input: ControllerInput::default(),
+            ground: ControllerGround::default(),
+            platform: ControllerPlatform::default(),
+            wall: ControllerWall::default(),
+            ledge: ControllerLedge::default(),
+            climb: ControllerClimb::default(),
+            fluid: ControllerFluid::default(),
+            abilities: ControllerAbilities::default(),
+            animation: CharacterAnimationParams::default(),
+            interpolation: RenderInterpolation::default(),
+            controller,
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Marks a sensor collider as a ladder or climbable surface, entering it
+/// switches the character to [`ControllerState::Climb`]
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Climbable {
+    /// Speed of climbing along the surface
+    pub speed: f32,
+    /// Speed away from the surface given by jumping off
+    pub push: f32,
+    /// Outward normal of the surface in the collider's local space
+    pub normal: Vec3,
+}
+
+impl Default for Climbable {
+    fn default() -> Self {
+        Climbable {
+            speed: 5.0,
+            push: 6.0,
+            normal: Vec3::Z,
+        }
+    }
+}
+
+impl Climbable {
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+    pub fn with_push(mut self, push: f32) -> Self {
+        self.push = push;
+        self
+    }
+    pub fn with_normal(mut self, normal: Vec3) -> Self {
+        self.normal = normal;
+        self
+    }
+}
+
+/// [`Climbable`] the character overlaps
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct ControllerClimb {
+    entity: Option<Entity>,
+    climbable: Option<Climbable>,
+    normal: Vec3,
+    entered: bool,
+}
+
+impl ControllerClimb {
+    pub fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+    pub fn climbable(&self) -> Option<&Climbable> {
+        self.climbable.as_ref()
+    }
+    /// Outward normal of the surface in world space
+    pub fn normal(&self) -> Option<Vec3> {
+        self.climbable.map(|_| self.normal)
+    }
+    /// Started overlapping a [`Climbable`] during this step
+    pub fn entered(&self) -> bool {
+        self.entered
+    }
+    /// Velocity given by jumping off the surface
+    pub fn push(&self) -> Vec3 {
+        self.climbable
+            .map_or(Vec3::ZERO, |climbable| self.normal * climbable.push)
+    }
+}
+
+/// Finds the [`Climbable`] sensor overlapping the character
+pub fn update_climb_probe(
+    mut q: Query<(
+        Entity,
+        &MovementController,
+        &mut ControllerClimb,
+        &Transform,
+    )>,
+    climbables: Query<(&Climbable, &GlobalTransform)>,
+    rapier: Option<Res<RapierContext>>,
+) {
+    for (entity, character, mut climb, transform) in q.iter_mut() {
+        let previous = climb.entity;
+        let mut found = None;
+
+        if let Some(rapier) = rapier.as_ref() {
+            intersect_capsule(rapier, entity, character, transform, |hit| {
+                found = climbables.get(hit).ok().map(|climbable| (hit, climbable));
+                found.is_none()
+            });
+        }
+
+        let up = character.up();
+        climb.entity = found.map(|(hit, _)| hit);
+        climb.climbable = found.map(|(_, (climbable, _))| *climbable);
+        climb.normal = found.map_or(Vec3::ZERO, |(_, (climbable, global))| {
+            let normal = global.compute_transform().rotation * climbable.normal;
+            (normal - up * normal.dot(up)).normalize_or_zero()
+        });
+        climb.entered = climb.entity.is_some() && climb.entity != previous;
+    }
+}
+
+/// Calls `callback` with the colliders overlapping the character's capsule until it
+/// returns `false`. Balls spaced along the capsule axis stand in for the capsule: with
+/// the capsule itself, a character sinking into a large cuboid sensor such as a
+/// [`FluidVolume`] drops in and out of it from one step to the next.
+pub(crate) fn intersect_capsule(
+    rapier: &RapierContext,
+    entity: Entity,
+    character: &MovementController,
+    transform: &Transform,
+    mut callback: impl FnMut(Entity) -> bool,
+) {
+    let axis = transform.rotation * Vec3::Y;
+    let height = character.current_height();
+    let steps = (height / character.radius()).ceil().max(1.0) as usize;
+    let ball = Collider::ball(character.radius());
+    let filter = QueryFilter::new().exclude_collider(entity);
+    let mut searching = true;
+
+    for step in 0..=steps {
+        let offset = height * (step as f32 / steps as f32 - 0.5);
+        rapier.intersections_with_shape(
+            transform.translation + axis * offset,
+            Quat::IDENTITY,
+            &ball,
+            filter,
+            |hit| {
+                searching = callback(hit);
+                searching
+            },
+        );
+        if !searching {
+            return;
+        }
+    }
+}
+
+/// Maps forward and back input to up and down along the surface while climbing
+pub fn update_climb_force(
+    mut q: Query<(
+        &mut MovementController,
+        &ControllerClimb,
+        &ControllerState,
+        &ActionState<CharacterMovement>,
+    )>,
+) {
+    for (mut character, climb, state, movement) in q.iter_mut() {
+        let (ControllerState::Climb, Some(climbable), Some(normal)) =
+            (state, climb.climbable(), climb.normal())
+        else {
+            continue;
+        };
+
+        let up = character.up();
+        let right = (-normal).cross(up);
+        let input = CharacterMovement::input(movement, character.stick());
+        let velocity = (up * -input.z + right * input.x) * climbable.speed;
+
+        let mut forces = *character.forces();
+        forces.set_movement(velocity);
+        character.set_forces(forces);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Checks whether a crouched capsule could grow back to full height where it stands,
+/// so the state machine keeps the character crouched under low ceilings
+pub fn update_headroom_probe(
+    mut q: Query<(Entity, &mut MovementController, &Collider, &Transform)>,
+    rapier: Option<Res<RapierContext>>,
+) {
+    for (entity, mut character, collider, transform) in q.iter_mut() {
+        let blocked = character.crouched()
+            && rapier.as_ref().is_some_and(|rapier| {
+                rapier
+                    .cast_shape(
+                        transform.translation,
+                        transform.rotation,
+                        character.up(),
+                        collider,
+                        character.height() - character.crouch().height,
+                        false,
+                        QueryFilter::new()
+                            .exclude_collider(entity)
+                            .exclude_sensors(),
+                    )
+                    .is_some()
+            });
+
+        if character.headroom_blocked() != blocked {
+            character.set_headroom_blocked(blocked);
+        }
+    }
+}
+
+/// Resizes the capsule when entering or leaving [`ControllerState::Crouch`] and
+/// [`ControllerState::Slide`], keeping the feet in place. Standing up waits for headroom.
+pub fn update_player_crouch(
+    mut q: Query<(
+        &mut MovementController,
+        &ControllerState,
+        &mut Collider,
+        &mut Transform,
+    )>,
+) {
+    for (mut character, state, mut collider, mut transform) in q.iter_mut() {
+        let crouching = matches!(*state, ControllerState::Crouch | ControllerState::Slide);
+        if crouching == character.crouched() {
+            continue;
+        }
+
+        let up = character.up();
+        let radius = character.radius();
+        let difference = character.height() - character.crouch().height;
+
+        if crouching {
+            *collider = Collider::capsule_y(character.crouch().height / 2., radius);
+            transform.translation -= up * difference / 2.;
+            character.set_crouched(true);
+            continue;
+        }
+
+        // Only states that can't stand, like falling, get here without headroom
+        if character.headroom_blocked() {
+            continue;
+        }
+
+        *collider = Collider::capsule_y(character.height() / 2., radius);
+        transform.translation += up * difference / 2.;
+        character.set_crouched(false);
+    }
+}
+
+/// Moves the eye height towards the capsule top and keeps the [`CameraTarget`] on it
+pub fn update_player_eye(
+    mut q: Query<(&mut MovementController, Option<&mut CameraTarget>)>,
+    time: Res<Time>,
+) {
+    for (mut character, target) in q.iter_mut() {
+        let t = 1.0 - (-character.crouch().eye_speed * time.delta_seconds()).exp();
+        let eye_height = character.eye_height();
+        let eye_height = eye_height + (character.target_eye_height() - eye_height) * t;
+        character.set_eye_height(eye_height);
+
+        if let Some(mut target) = target {
+            // Translation sits at the capsule center, half the full height above the feet
+            let center = character.current_height() / 2. + character.radius();
+            target.set_offset(character.up() * (eye_height - center));
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+/// Sent whenever a character's [`ControllerState`] changes, including changes made
+/// directly by other systems and re-entering a state such as chained air jumps
+#[derive(Event, Debug, Clone)]
+pub struct ControllerStateChanged {
+    pub entity: Entity,
+    pub from: ControllerState,
+    pub to: ControllerState,
+}
+
+/// Sent when a character leaves the ground or a wall with any kind of jump
+#[derive(Event, Debug, Clone)]
+pub struct Jumped {
+    pub entity: Entity,
+    /// [`ControllerState::Jump`], [`ControllerState::WallJump`] or [`ControllerState::AirJump`]
+    pub state: ControllerState,
+}
+
+/// Sent when a character touches the ground after being airborne
+#[derive(Event, Debug, Clone)]
+pub struct Landed {
+    pub entity: Entity,
+    /// Velocity right before touching the ground
+    pub velocity: Vec3,
+    /// Seconds spent off the ground
+    pub air_time: f32,
+}
+
+/// Sent when a character starts a [`ControllerState::Slide`]
+#[derive(Event, Debug, Clone)]
+pub struct SlideStarted {
+    pub entity: Entity,
+}
+
+/// Sent when a character leaves [`ControllerState::Slide`]
+#[derive(Event, Debug, Clone)]
+pub struct SlideStopped {
+    pub entity: Entity,
+}
+
+/// Sent every [`ControllerGroundSettings::stride`] walked on the ground
+#[derive(Event, Debug, Clone)]
+pub struct Footstep {
+    pub entity: Entity,
+    /// Point below the character's feet
+    pub position: Vec3,
+    /// Tag of the [`Surface`] stepped on
+    pub surface: Option<String>,
+}
+
+/// Writers for the events derived from a state change
+#[derive(SystemParam)]
+pub struct ControllerStateEvents<'w> {
+    changed: EventWriter<'w, ControllerStateChanged>,
+    jumped: EventWriter<'w, Jumped>,
+    slide_started: EventWriter<'w, SlideStarted>,
+    slide_stopped: EventWriter<'w, SlideStopped>,
+    dashed: EventWriter<'w, Dashed>,
+    air_jumped: EventWriter<'w, AirJumped>,
+}
+
+impl ControllerStateEvents<'_> {
+    /// Events for `context` having just entered its state from `from`
+    pub(crate) fn send(&mut self, context: &StateContext, from: ControllerState) {
+        use ControllerState::*;
+        let (entity, to) = (context.entity, context.state);
+
+        self.changed
+            .send(ControllerStateChanged { entity, from, to });
+
+        if matches!(to, Jump | WallJump | AirJump) {
+            self.jumped.send(Jumped { entity, state: to });
+        }
+        if to == Slide {
+            self.slide_started.send(SlideStarted { entity });
+        }
+        if from == Slide {
+            self.slide_stopped.send(SlideStopped { entity });
+        }
+        if to == Dash {
+            let direction = context.abilities.dash_direction();
+            self.dashed.send(Dashed { entity, direction });
+        }
+        if let Some(air_jump) = context.character.air_jump().filter(|_| to == AirJump) {
+            let remaining = context.abilities.air_jump().available(&air_jump.charges);
+            self.air_jumped.send(AirJumped { entity, remaining });
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Marks a sensor collider as water or another fluid the character can swim in
+#[derive(Component, Debug, Clone, Copy)]
+pub struct FluidVolume {
+    /// Density relative to the character, above 1 a fully submerged character floats up
+    pub density: f32,
+    /// Rate at which the fluid slows the character down, per second
+    pub drag: f32,
+    /// Height of the surface above the collider's origin
+    pub surface: f32,
+}
+
+impl Default for FluidVolume {
+    fn default() -> Self {
+        FluidVolume {
+            density: 1.5,
+            drag: 2.0,
+            surface: 0.0,
+        }
+    }
+}
+
+impl FluidVolume {
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+    pub fn with_drag(mut self, drag: f32) -> Self {
+        self.drag = drag;
+        self
+    }
+    pub fn with_surface(mut self, surface: f32) -> Self {
+        self.surface = surface;
+        self
+    }
+}
+
+/// [`FluidVolume`] the character overlaps and how deep it is in it
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct ControllerFluid {
+    entity: Option<Entity>,
+    volume: Option<FluidVolume>,
+    depth: f32,
+    submerged: f32,
+}
+
+impl ControllerFluid {
+    pub fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+    pub fn volume(&self) -> Option<&FluidVolume> {
+        self.volume.as_ref()
+    }
+    /// Height of the surface above the feet, zero outside of any fluid
+    pub fn depth(&self) -> f32 {
+        self.depth
+    }
+    /// Fraction of the capsule below the surface
+    pub fn submerged(&self) -> f32 {
+        self.submerged
+    }
+    /// Whole capsule is below the surface
+    pub fn underwater(&self) -> bool {
+        self.submerged >= 1.0
+    }
+    /// Fraction of gravity left after buoyancy, negative when floating up
+    pub fn gravity_scale(&self) -> f32 {
+        self.volume
+            .map_or(1.0, |volume| 1.0 - volume.density * self.submerged)
+    }
+}
+
+/// Finds the [`FluidVolume`] sensor overlapping the character and its surface height
+pub fn update_fluid_probe(
+    mut q: Query<(
+        Entity,
+        &MovementController,
+        &mut ControllerFluid,
+        &Transform,
+    )>,
+    volumes: Query<(&FluidVolume, &GlobalTransform)>,
+    rapier: Option<Res<RapierContext>>,
+) {
+    for (entity, character, mut fluid, transform) in q.iter_mut() {
+        let mut found = None;
+
+        if let Some(rapier) = rapier.as_ref() {
+            intersect_capsule(rapier, entity, character, transform, |hit| {
+                found = volumes.get(hit).ok().map(|volume| (hit, volume));
+                found.is_none()
+            });
+        }
+
+        let up = character.up();
+        let height = character.current_height() + character.radius() * 2.;
+        let feet = transform.translation - up * height / 2.;
+        let depth = found.map_or(0.0, |(_, (volume, global))| {
+            let surface = global.translation() + up * volume.surface;
+            (surface - feet).dot(up).max(0.0)
+        });
+
+        fluid.entity = found.map(|(hit, _)| hit);
+        fluid.volume = found.map(|(_, (volume, _))| *volume);
+        fluid.depth = depth;
+        fluid.submerged = (depth / height).min(1.0);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+/// Debug overlay drawn over every character, independent of Rapier's debug render.
+/// Added by [`BoxyControllerPlugin::gizmos`].
+#[derive(Resource, Debug, Clone)]
+pub struct ControllerGizmos {
+    pub enabled: bool,
+    /// Outline of the collider, green on the ground and orange in the air
+    pub capsule: bool,
+    /// Ray along the current velocity
+    pub velocity: bool,
+    /// Ray along the probed ground normal
+    pub ground_normal: bool,
+    /// Name of the [`ControllerState`] above the head
+    pub state_label: bool,
+    /// Camera the labels are placed for, `None` uses the first active camera
+    pub camera: Option<Entity>,
+    /// Key flipping [`Self::enabled`]
+    pub toggle_key: Option<KeyCode>,
+}
+
+impl Default for ControllerGizmos {
+    fn default() -> Self {
+        ControllerGizmos {
+            enabled: true,
+            capsule: true,
+            velocity: true,
+            ground_normal: true,
+            state_label: true,
+            camera: None,
+            toggle_key: Some(KeyCode::F2),
+        }
+    }
+}
+
+/// UI text following the character it labels
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ControllerStateLabel {
+    target: Entity,
+}
+
+impl ControllerStateLabel {
+    pub fn target(&self) -> Entity {
+        self.target
+    }
+}
+
+pub fn toggle_controller_gizmos(
+    mut settings: ResMut<ControllerGizmos>,
+    keys: Option<Res<Input<KeyCode>>>,
+) {
+    let (Some(key), Some(keys)) = (settings.toggle_key, keys) else {
+        return;
+    };
+    if keys.just_pressed(key) {
+        settings.enabled = !settings.enabled;
+    }
+}
+
+pub fn draw_controller_gizmos(
+    mut gizmos: Gizmos,
+    settings: Res<ControllerGizmos>,
+    q: Query<(&MovementController, &ControllerGround, &Transform)>,
+) {
+    if !settings.enabled {
+        return;
+    }
+
+    for (character, ground, transform) in q.iter() {
+        let up = character.up();
+        let center = transform.translation;
+        let radius = character.radius();
+        let half = character.current_height() / 2.;
+        let feet = center - up * (half + radius);
+
+        if settings.capsule {
+            let color = if character.grounded() {
+                Color::GREEN
+            } else {
+                Color::ORANGE
+            };
+            let top = center + up * half;
+            let bottom = center - up * half;
+            let rotation = transform.rotation;
+            gizmos.sphere(top, rotation, radius, color);
+            gizmos.sphere(bottom, rotation, radius, color);
+            for side in [Vec3::X, Vec3::NEG_X, Vec3::Z, Vec3::NEG_Z] {
+                let side = rotation * side * radius;
+                gizmos.line(top + side, bottom + side, color);
+            }
+        }
+
+        if settings.velocity {
+            gizmos.ray(center, character.velocity() * 0.25, Color::CYAN);
+        }
+
+        if settings.ground_normal && ground.entity().is_some() {
+            gizmos.ray(feet, ground.normal(), Color::FUCHSIA);
+        }
+    }
+}
+
+/// Spawns, moves and removes the [`ControllerStateLabel`] of every character
+pub fn update_state_labels(
+    mut commands: Commands,
+    settings: Res<ControllerGizmos>,
+    characters: Query<(Entity, &MovementController, &ControllerState, &Transform)>,
+    mut labels: Query<(
+        Entity,
+        &ControllerStateLabel,
+        &mut Text,
+        &mut Style,
+        &mut Visibility,
+    )>,
+    cameras: Query<(Entity, &bevy::render::camera::Camera, &GlobalTransform)>,
+) {
+    if !(settings.enabled && settings.state_label) {
+        for (.., mut visibility) in labels.iter_mut() {
+            visibility.set_if_neq(Visibility::Hidden);
+        }
+        return;
+    }
+
+    let camera = cameras
+        .iter()
+        .find(|(entity, camera, _)| match settings.camera {
+            Some(target) => *entity == target,
+            None => camera.is_active,
+        });
+    let mut labelled = HashSet::new();
+
+    for (entity, label, mut text, mut style, mut visibility) in labels.iter_mut() {
+        let Ok((_, character, state, transform)) = characters.get(label.target) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        labelled.insert(label.target);
+
+        let head = transform.translation
+            + character.up() * (character.current_height() / 2. + character.radius() + 0.3);
+        let position =
+            camera.and_then(|(_, camera, global)| camera.world_to_viewport(global, head));
+        let Some(position) = position else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Inherited;
+        style.left = Val::Px(position.x);
+        style.top = Val::Px(position.y);
+        if let Some(section) = text.sections.first_mut() {
+            let name = format!("{state:?}");
+            if section.value != name {
+                section.value = name;
+            }
+        }
+    }
+
+    for (entity, ..) in characters.iter() {
+        if labelled.contains(&entity) {
+            continue;
+        }
+        commands.spawn((
+            TextBundle::from_section(
+                String::new(),
+                TextStyle {
+                    font_size: 16.0,
+                    color: Color::WHITE,
+                    ..Default::default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            }),
+            ControllerStateLabel { target: entity },
+        ));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Optional tag and friction of a collider the character can stand on,
+/// looked up on the hit collider and then on its parent
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Surface {
+    /// User defined name, e.g. `"ice"` or `"mud"`
+    pub tag: String,
+    /// Multiplier on ground acceleration and friction, lower is more slippery
+    pub friction: f32,
+}
+
+impl Default for Surface {
+    fn default() -> Self {
+        Surface {
+            tag: String::new(),
+            friction: 1.0,
+        }
+    }
+}
+
+impl Surface {
+    pub fn new(tag: impl Into<String>) -> Self {
+        Surface {
+            tag: tag.into(),
+            ..Default::default()
+        }
+    }
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+}
+
+/// Ground below the character, probed at the start of every controller step
+#[derive(Component, Debug, Clone)]
+pub struct ControllerGround {
+    grounded: bool,
+    normal: Vec3,
+    slope_angle: f32,
+    entity: Option<Entity>,
+    distance: Option<f32>,
+    since_grounded: f32,
+    surface: Option<Surface>,
+    stride: f32,
+}
+
+impl Default for ControllerGround {
+    fn default() -> Self {
+        Self {
+            grounded: false,
+            normal: Vec3::Y,
+            slope_angle: 0.0,
+            entity: None,
+            distance: None,
+            since_grounded: f32::INFINITY,
+            surface: None,
+            stride: 0.0,
+        }
+    }
+}
+
+impl ControllerGround {
+    pub fn grounded(&self) -> bool {
+        self.grounded
+    }
+    /// Normal of the probed ground, up when nothing is below
+    pub fn normal(&self) -> Vec3 {
+        self.normal
+    }
+    /// Angle between the ground normal and up, in radians
+    pub fn slope_angle(&self) -> f32 {
+        self.slope_angle
+    }
+    /// Collider below the character, if any is within the probe distance
+    pub fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+    /// Gap between the capsule and the ground below it
+    pub fn distance(&self) -> Option<f32> {
+        self.distance
+    }
+    /// Seconds since the character was last grounded
+    pub fn since_grounded(&self) -> f32 {
+        self.since_grounded
+    }
+    pub fn surface(&self) -> Option<&Surface> {
+        self.surface.as_ref()
+    }
+    /// Tag of the [`Surface`] stood on
+    pub fn surface_tag(&self) -> Option<&str> {
+        self.surface.as_ref().map(|surface| surface.tag.as_str())
+    }
+    /// Friction of the [`Surface`] stood on, `1.0` without one
+    pub fn friction(&self) -> f32 {
+        self.surface
+            .as_ref()
+            .map_or(1.0, |surface| surface.friction)
+    }
+}
+
+pub(crate) const PROBE_SKIN: f32 = 0.1;
+
+/// Casts the capsule down to find the ground and updates [`MovementController::grounded`]
+pub fn update_ground_probe(
+    mut q: Query<(
+        Entity,
+        &mut MovementController,
+        &mut ControllerGround,
+        &KinematicCharacterController,
+        &KinematicCharacterControllerOutput,
+        &Collider,
+        &Transform,
+    )>,
+    surfaces: Query<&Surface>,
+    parents: Query<&Parent>,
+    rapier: Option<Res<RapierContext>>,
+    mut landed: EventWriter<Landed>,
+    time: Res<Time>,
+) {
+    for (entity, mut character, mut ground, controller, physics, collider, transform) in
+        q.iter_mut()
+    {
+        let up = character.up();
+        let settings = *character.ground();
+
+        // Starting slightly above avoids the unreliable normals of a cast already in contact
+        let hit = rapier.as_ref().and_then(|rapier| {
+            rapier.cast_shape(
+                transform.translation + up * PROBE_SKIN,
+                transform.rotation,
+                -up,
+                collider,
+                settings.probe_distance + PROBE_SKIN,
+                false,
+                QueryFilter::new()
+                    .exclude_collider(entity)
+                    .exclude_sensors(),
+            )
+        });
+
+        ground.entity = hit.map(|(hit, _)| hit);
+        ground.distance = hit.map(|(_, toi)| (toi.toi - PROBE_SKIN).max(0.0));
+        ground.normal = hit
+            .and_then(|(_, toi)| toi.details)
+            .map_or(up, |details| details.normal1.normalize_or_zero());
+        ground.slope_angle = ground.normal.angle_between(up);
+        ground.surface = ground.entity.and_then(|hit| {
+            surfaces
+                .get(hit)
+                .or_else(|_| surfaces.get(parents.get(hit)?.get()))
+                .ok()
+                .cloned()
+        });
+
+        let rising = character.forces().gravity().dot(up) > 0.0;
+        let near = ground
+            .distance
+            .is_some_and(|distance| distance <= settings.grounded_distance);
+        let walkable = ground.slope_angle <= controller.max_slope_climb_angle;
+        let was_grounded = ground.grounded;
+        ground.grounded = !rising && (physics.grounded || near && walkable);
+
+        // Characters spawned in the air have no ground to have left
+        if ground.grounded && !was_grounded && ground.since_grounded.is_finite() {
+            landed.send(Landed {
+                entity,
+                velocity: character.velocity(),
+                air_time: ground.since_grounded,
+            });
+        }
+
+        if ground.grounded {
+            ground.since_grounded = 0.0;
+        } else {
+            ground.since_grounded += time.delta_seconds();
+        }
+
+        character.set_grounded(ground.grounded);
+    }
+}
+
+/// Sends a [`Footstep`] every [`ControllerGroundSettings::stride`] walked on the ground
+pub fn update_footsteps(
+    mut q: Query<(
+        Entity,
+        &MovementController,
+        &mut ControllerGround,
+        &ControllerState,
+        &KinematicCharacterControllerOutput,
+        &Transform,
+    )>,
+    mut footsteps: EventWriter<Footstep>,
+) {
+    for (entity, character, mut ground, state, physics, transform) in q.iter_mut() {
+        if !ground.grounded || *state == ControllerState::Slide {
+            ground.stride = 0.0;
+            continue;
+        }
+
+        let up = character.up();
+        let moved = physics.effective_translation;
+        ground.stride += (moved - up * moved.dot(up)).length();
+
+        let stride = character.ground().stride;
+        if stride <= 0.0 || ground.stride < stride {
+            continue;
+        }
+
+        ground.stride %= stride;
+        let half = character.current_height() / 2.;
+        footsteps.send(Footstep {
+            entity,
+            position: transform.translation - up * (half + character.radius()),
+            surface: ground.surface_tag().map(str::to_owned),
+        });
+    }
+}
+
+/// Tilts `velocity` onto the plane of `normal`, keeping its heading and length
+pub(crate) fn project_on_ground(velocity: Vec3, normal: Vec3, up: Vec3) -> Vec3 {
+    let along = normal.dot(up);
+    if along <= f32::EPSILON {
+        return velocity;
+    }
+
+    let planar = velocity - up * velocity.dot(up);
+    let projected = planar - up * (planar.dot(normal) / along);
+    projected.normalize_or_zero() * velocity.length()
+}
@@ -0,0 +1,41 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// [`CharacterActions`] latched every frame until the next fixed controller step,
+/// so presses shorter than a fixed step are not lost
+#[derive(Component, Default, Debug, Clone)]
+pub struct ControllerInput {
+    pressed: Vec<CharacterActions>,
+    just_pressed: Vec<CharacterActions>,
+    just_released: Vec<CharacterActions>,
+}
+
+impl ControllerInput {
+    pub fn pressed(&self, action: CharacterActions) -> bool {
+        self.pressed.contains(&action)
+    }
+    pub fn just_pressed(&self, action: CharacterActions) -> bool {
+        self.just_pressed.contains(&action)
+    }
+    pub fn just_released(&self, action: CharacterActions) -> bool {
+        self.just_released.contains(&action)
+    }
+}
+
+pub(crate) fn latch_controller_input(
+    mut q: Query<(&mut ControllerInput, &ActionState<CharacterActions>)>,
+) {
+    for (mut input, actions) in q.iter_mut() {
+        input.pressed = actions.get_pressed();
+        input.just_pressed.extend(actions.get_just_pressed());
+        input.just_released.extend(actions.get_just_released());
+    }
+}
+
+pub(crate) fn consume_controller_input(mut q: Query<&mut ControllerInput>) {
+    for mut input in q.iter_mut() {
+        input.just_pressed.clear();
+        input.just_released.clear();
+    }
+}
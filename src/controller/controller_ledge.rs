@@ -0,0 +1,166 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Ledge in front of the character, probed while airborne
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct ControllerLedge {
+    top: Option<Vec3>,
+    normal: Option<Vec3>,
+    target: Option<Vec3>,
+    mantle_from: Vec3,
+    mantle_progress: f32,
+    since_released: f32,
+}
+
+impl ControllerLedge {
+    /// Point on top of the ledge
+    pub fn top(&self) -> Option<Vec3> {
+        self.top
+    }
+    /// Normal of the wall below the ledge, pointing towards the character
+    pub fn normal(&self) -> Option<Vec3> {
+        self.normal
+    }
+    /// Position of the character standing on the ledge after mantling
+    pub fn target(&self) -> Option<Vec3> {
+        self.target
+    }
+    /// Fraction of the current mantle already done
+    pub fn mantle_progress(&self) -> f32 {
+        self.mantle_progress
+    }
+    /// A ledge with room to stand on it is within reach
+    pub fn can_grab(&self) -> bool {
+        self.target.is_some()
+    }
+}
+
+/// Looks for a wall at chest height and a walkable top within
+/// [`ControllerLedgeSettings::max_height`] above the feet
+pub fn update_ledge_probe(
+    mut q: Query<(
+        Entity,
+        &MovementController,
+        &mut ControllerLedge,
+        &ControllerState,
+        &Collider,
+        &Transform,
+    )>,
+    rapier: Option<Res<RapierContext>>,
+    time: Res<Time>,
+) {
+    for (entity, character, mut ledge, state, collider, transform) in q.iter_mut() {
+        match state {
+            ControllerState::Hang | ControllerState::Mantle => {
+                ledge.since_released = 0.0;
+                continue;
+            }
+            ControllerState::Jump | ControllerState::AirJump | ControllerState::Fall => {
+                ledge.since_released += time.delta_seconds();
+            }
+            _ => ledge.since_released = f32::INFINITY,
+        }
+
+        ledge.top = None;
+        ledge.normal = None;
+        ledge.target = None;
+
+        let settings = *character.ledge();
+        let airborne = matches!(
+            state,
+            ControllerState::Jump | ControllerState::AirJump | ControllerState::Fall
+        );
+        let Some(rapier) = rapier.as_ref().filter(|_| airborne) else {
+            continue;
+        };
+        if ledge.since_released < settings.cooldown {
+            continue;
+        }
+
+        let up = character.up();
+        let movement = character.forces().movement();
+        let forward = (movement - up * movement.dot(up))
+            .try_normalize()
+            .unwrap_or(transform.forward());
+        let filter = QueryFilter::new()
+            .exclude_collider(entity)
+            .exclude_sensors();
+
+        let half = character.current_height() / 2.;
+        let feet = transform.translation - up * (half + character.radius());
+        let chest = transform.translation + up * half;
+
+        let Some((_, wall)) = rapier.cast_ray_and_get_normal(
+            chest,
+            forward,
+            character.radius() + settings.reach,
+            true,
+            filter,
+        ) else {
+            continue;
+        };
+        if wall.normal.dot(up).abs() > 0.3 {
+            continue;
+        }
+
+        // Look down onto the ledge from the highest reachable point, just past the wall
+        let above = feet + up * settings.max_height + forward * (wall.toi + 0.2);
+        let Some((_, top)) =
+            rapier.cast_ray_and_get_normal(above, -up, (above - chest).dot(up), true, filter)
+        else {
+            continue;
+        };
+        if top.toi <= 0.0 || top.normal.dot(up) < 0.7 {
+            continue;
+        }
+
+        let target =
+            top.point + forward * character.radius() + up * (half + character.radius() + 0.05);
+        if rapier
+            .intersection_with_shape(target, transform.rotation, collider, filter)
+            .is_some()
+        {
+            continue;
+        }
+
+        ledge.top = Some(top.point);
+        ledge.normal = Some((wall.normal - up * wall.normal.dot(up)).normalize_or_zero());
+        ledge.target = Some(target);
+    }
+}
+
+/// Moves a [`ControllerState::Mantle`] up to the ledge height and then over it
+pub fn update_player_mantle(
+    mut q: Query<(
+        &MovementController,
+        &mut ControllerLedge,
+        &ControllerState,
+        &mut Transform,
+    )>,
+    time: Res<Time>,
+) {
+    for (character, mut ledge, state, mut transform) in q.iter_mut() {
+        let (ControllerState::Mantle, Some(target)) = (state, ledge.target) else {
+            ledge.mantle_progress = 0.0;
+            continue;
+        };
+
+        if ledge.mantle_progress == 0.0 {
+            ledge.mantle_from = transform.translation;
+        }
+
+        let duration = character.ledge().duration.max(f32::EPSILON);
+        ledge.mantle_progress = (ledge.mantle_progress + time.delta_seconds() / duration).min(1.0);
+
+        let up = character.up();
+        let from = ledge.mantle_from;
+        let raised = from + up * (target - from).dot(up);
+        let t = ledge.mantle_progress * 2.0;
+        transform.translation = if t < 1.0 {
+            from.lerp(raised, t)
+        } else {
+            raised.lerp(target, t - 1.0)
+        };
+    }
+}
@@ -0,0 +1,152 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// Visible body attached to a character as a child, see
+/// [`BoxyControllerPlugin::player_model`] for the default player
+#[derive(Component, Debug, Clone)]
+pub enum CharacterModel {
+    /// Capsule mesh matching the collider, squashed while crouching
+    Capsule { color: Color },
+    /// glTF or other scene placed at `offset` from the capsule center
+    Scene {
+        scene: Handle<Scene>,
+        offset: Transform,
+    },
+}
+
+impl Default for CharacterModel {
+    fn default() -> Self {
+        CharacterModel::Capsule {
+            color: Color::rgb(0.8, 0.7, 0.6),
+        }
+    }
+}
+
+impl CharacterModel {
+    /// `scene` with its origin at the feet of a standing `character`
+    pub fn scene_at_feet(scene: Handle<Scene>, character: &MovementController) -> Self {
+        let feet = character.height() / 2. + character.radius();
+        CharacterModel::Scene {
+            scene,
+            offset: Transform::from_xyz(0.0, -feet, 0.0),
+        }
+    }
+}
+
+/// Child entity showing the [`CharacterModel`] of its parent
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CharacterModelRoot {
+    offset: Vec3,
+}
+
+/// Spawns the model of newly added or changed [`CharacterModel`]s
+pub fn spawn_character_models(
+    mut commands: Commands,
+    q: Query<
+        (
+            Entity,
+            &CharacterModel,
+            &MovementController,
+            Option<&Children>,
+            Has<Visibility>,
+        ),
+        Changed<CharacterModel>,
+    >,
+    roots: Query<(), With<CharacterModelRoot>>,
+    mut meshes: Option<ResMut<Assets<Mesh>>>,
+    mut materials: Option<ResMut<Assets<StandardMaterial>>>,
+) {
+    for (entity, model, character, children, visible) in q.iter() {
+        let root = match model {
+            CharacterModel::Capsule { color } => {
+                // Built from assets that headless apps may not have
+                let (Some(meshes), Some(materials)) = (meshes.as_mut(), materials.as_mut()) else {
+                    continue;
+                };
+                let mesh = Mesh::from(shape::Capsule {
+                    radius: character.radius(),
+                    depth: character.height(),
+                    ..Default::default()
+                });
+                commands
+                    .spawn((
+                        PbrBundle {
+                            mesh: meshes.add(mesh),
+                            material: materials.add((*color).into()),
+                            ..Default::default()
+                        },
+                        CharacterModelRoot { offset: Vec3::ZERO },
+                    ))
+                    .id()
+            }
+            CharacterModel::Scene { scene, offset } => commands
+                .spawn((
+                    SceneBundle {
+                        scene: scene.clone(),
+                        transform: *offset,
+                        ..Default::default()
+                    },
+                    CharacterModelRoot {
+                        offset: offset.translation,
+                    },
+                ))
+                .id(),
+        };
+
+        for child in children.into_iter().flatten() {
+            if roots.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+        // Children only render below parents taking part in visibility propagation
+        if !visible {
+            commands.entity(entity).insert(VisibilityBundle::default());
+        }
+        commands.entity(entity).add_child(root);
+    }
+}
+
+/// Despawns the model of characters whose [`CharacterModel`] was removed
+pub fn despawn_removed_character_models(
+    mut commands: Commands,
+    mut removed: RemovedComponents<CharacterModel>,
+    children: Query<&Children>,
+    roots: Query<(), With<CharacterModelRoot>>,
+) {
+    for entity in removed.read() {
+        let Ok(children) = children.get(entity) else {
+            continue;
+        };
+        for child in children.iter() {
+            if roots.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+    }
+}
+
+/// Keeps the model on the ground while the capsule shrinks for a crouch
+pub fn update_character_models(
+    characters: Query<(&MovementController, &CharacterModel)>,
+    mut roots: Query<(&Parent, &CharacterModelRoot, &mut Transform)>,
+) {
+    for (parent, root, mut transform) in roots.iter_mut() {
+        let Ok((character, model)) = characters.get(parent.get()) else {
+            continue;
+        };
+
+        // The collider shrinks around a center that moved down by half the difference
+        let standing = character.height() + 2. * character.radius();
+        let current = character.current_height() + 2. * character.radius();
+        let drop = (character.height() - character.current_height()) / 2.;
+
+        match model {
+            CharacterModel::Capsule { .. } => {
+                transform.scale.y = current / standing;
+            }
+            CharacterModel::Scene { .. } => {
+                transform.translation = root.offset + Vec3::Y * drop;
+            }
+        }
+    }
+}
@@ -1,12 +1,16 @@
+use crate::prelude::*;
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Actionlike, Reflect, Clone, Hash, Debug, Copy, PartialEq, Eq)]
+#[derive(Actionlike, Reflect, Clone, Hash, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CharacterMovement {
     Left,
     Right,
     Back,
     Forward,
+    /// Analog stick movement
+    Move,
 }
 
 impl CharacterMovement {
@@ -16,6 +20,43 @@ impl CharacterMovement {
             CharacterMovement::Left => Vec3::NEG_X,
             CharacterMovement::Back => Vec3::Z,
             CharacterMovement::Forward => Vec3::NEG_Z,
+            CharacterMovement::Move => Vec3::ZERO,
         }
     }
+
+    /// Analog stick tilt after the deadzone and response curve
+    pub fn analog(actions: &ActionState<Self>, stick: &StickResponse) -> Vec2 {
+        let axis = actions
+            .axis_pair(CharacterMovement::Move)
+            .unwrap_or_default();
+        stick.apply(axis.xy())
+    }
+
+    /// Local movement direction from digital and analog input, with length up to 1
+    pub fn input(actions: &ActionState<Self>, stick: &StickResponse) -> Vec3 {
+        let digital = actions
+            .get_pressed()
+            .iter()
+            .map(|movement| movement.into_vec())
+            .sum::<Vec3>()
+            .clamp_length_max(1.0);
+        let analog = Self::analog(actions, stick);
+
+        (digital + Vec3::new(analog.x, 0.0, -analog.y)).clamp_length_max(1.0)
+    }
+}
+
+impl DefaultBindings for CharacterMovement {
+    fn default_input_map() -> InputMap<Self> {
+        InputMap::default()
+            .insert(KeyCode::W, CharacterMovement::Forward)
+            .insert(KeyCode::A, CharacterMovement::Left)
+            .insert(KeyCode::S, CharacterMovement::Back)
+            .insert(KeyCode::D, CharacterMovement::Right)
+            .insert(
+                StickResponse::raw(DualAxis::left_stick()),
+                CharacterMovement::Move,
+            )
+            .build()
+    }
 }
@@ -0,0 +1,81 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Body the character is riding, moved along with it between controller steps
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct ControllerPlatform {
+    entity: Option<Entity>,
+    transform: Transform,
+    velocity: Vec3,
+}
+
+impl ControllerPlatform {
+    /// Rigid body below the character, or the collider itself when it has none
+    pub fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+    /// Velocity the platform gave the character during the last step
+    pub fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+}
+
+/// Carries the character along with the translation and yaw of the body it stands on.
+/// Leaving the platform keeps its velocity as momentum.
+pub fn update_player_platform(
+    mut q: Query<(
+        &mut MovementController,
+        &mut ControllerPlatform,
+        &ControllerGround,
+        &mut Transform,
+    )>,
+    platforms: Query<&Transform, Without<MovementController>>,
+    rapier: Option<Res<RapierContext>>,
+    time: Res<Time>,
+) {
+    for (mut character, mut platform, ground, mut transform) in q.iter_mut() {
+        let body = ground.entity().filter(|_| ground.grounded()).map(|entity| {
+            rapier
+                .as_ref()
+                .and_then(|rapier| rapier.collider_parent(entity))
+                .unwrap_or(entity)
+        });
+        let current = body.and_then(|body| platforms.get(body).ok().copied());
+
+        match (current, body == platform.entity) {
+            (Some(current), true) => {
+                let previous = platform.transform;
+                let rotation = current.rotation * previous.rotation.inverse();
+                let offset = transform.translation - previous.translation;
+                let target = current.translation + rotation * offset;
+
+                platform.velocity = (target - transform.translation) / time.delta_seconds();
+                transform.translation = target;
+                transform.rotation = twist(rotation, character.up()) * transform.rotation;
+            }
+            (None, _) if platform.entity.is_some() => {
+                let up = character.up();
+                let velocity = platform.velocity;
+                let mut forces = *character.forces();
+                forces.set_movement(forces.movement() + velocity - up * velocity.dot(up));
+                forces.set_gravity(forces.gravity() + up * velocity.dot(up));
+                character.set_forces(forces);
+                platform.velocity = Vec3::ZERO;
+            }
+            _ => platform.velocity = Vec3::ZERO,
+        }
+
+        platform.entity = current.and(body);
+        platform.transform = current.unwrap_or_default();
+    }
+}
+
+/// Part of `rotation` turning around `axis`
+fn twist(rotation: Quat, axis: Vec3) -> Quat {
+    let projected = axis * Vec3::new(rotation.x, rotation.y, rotation.z).dot(axis);
+    projected
+        .extend(rotation.w)
+        .try_normalize()
+        .map_or(Quat::IDENTITY, Quat::from_vec4)
+}
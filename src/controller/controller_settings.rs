@@ -1,7 +1,7 @@
 use crate::prelude::*;
 use bevy::prelude::*;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, Copy)]
 pub struct ControllerForces {
     gravity: Vec3,
     movement: Vec3,
@@ -40,16 +40,289 @@ pub struct ControllerSpeedSettings {
     pub base: ControllerSpeed,
     pub run: ControllerSpeed,
     pub crouch: ControllerSpeed,
+    /// Speed a slide can't exceed, even downhill
     pub slide: ControllerSpeed,
+    pub swim: ControllerSpeed,
 }
 
 impl Default for ControllerSpeedSettings {
     fn default() -> Self {
-        return ControllerSpeedSettings {
+        ControllerSpeedSettings {
             base: ControllerSpeed(10.),
             run: ControllerSpeed(20.),
             crouch: ControllerSpeed(5.),
             slide: ControllerSpeed(25.),
-        };
+            swim: ControllerSpeed(6.),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerJumpSettings {
+    /// Seconds after leaving the ground during which a jump is still allowed
+    pub coyote_time: f32,
+    /// Seconds a jump press is remembered before landing
+    pub buffer_time: f32,
+    /// Upward velocity multiplier applied when jump is released early
+    pub release_multiplier: f32,
+}
+
+impl Default for ControllerJumpSettings {
+    fn default() -> Self {
+        ControllerJumpSettings {
+            coyote_time: 0.1,
+            buffer_time: 0.15,
+            release_multiplier: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerJumpTimers {
+    pub since_grounded: f32,
+    pub since_pressed: f32,
+    /// Jump button held since the jump started
+    pub held: bool,
+}
+
+impl Default for ControllerJumpTimers {
+    fn default() -> Self {
+        ControllerJumpTimers {
+            since_grounded: f32::INFINITY,
+            since_pressed: f32::INFINITY,
+            held: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerAccelerationSettings {
+    /// Rate at which grounded input reaches the target speed, in units/s²
+    pub ground_acceleration: f32,
+    /// Rate at which the character stops on the ground without input, in units/s²
+    pub ground_friction: f32,
+    /// Rate at which airborne input reaches the target speed, in units/s²
+    pub air_acceleration: f32,
+    /// Fraction of [`Self::air_acceleration`] available to the player
+    pub air_control: f32,
+}
+
+impl Default for ControllerAccelerationSettings {
+    fn default() -> Self {
+        ControllerAccelerationSettings {
+            ground_acceleration: 100.0,
+            ground_friction: 80.0,
+            air_acceleration: 60.0,
+            air_control: 0.5,
+        }
     }
 }
+
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerCrouchSettings {
+    /// Capsule segment height while crouching, see [`MovementController::height`]
+    pub height: f32,
+    /// Rate at which the eye height follows the capsule, higher is snappier
+    pub eye_speed: f32,
+}
+
+impl Default for ControllerCrouchSettings {
+    fn default() -> Self {
+        ControllerCrouchSettings {
+            height: 0.5,
+            eye_speed: 12.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerSlideSettings {
+    /// Speed added along the movement direction when a slide starts
+    pub boost: f32,
+    /// Rate at which a slide slows down on flat ground, in units/s²
+    pub friction: f32,
+    /// Fraction of gravity along the ground slope that speeds the slide up downhill
+    pub slope_acceleration: f32,
+    /// Speed below which a slide ends on its own, also required to start one
+    pub min_speed: f32,
+    /// Seconds after a slide ends before the next one can start
+    pub cooldown: f32,
+}
+
+impl Default for ControllerSlideSettings {
+    fn default() -> Self {
+        ControllerSlideSettings {
+            boost: 5.0,
+            friction: 10.0,
+            slope_acceleration: 1.0,
+            min_speed: 6.0,
+            cooldown: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerGroundSettings {
+    /// Distance below the capsule searched for ground
+    pub probe_distance: f32,
+    /// Gap below the capsule within which the character still counts as grounded
+    pub grounded_distance: f32,
+    /// Distance walked between two [`Footstep`] events
+    pub stride: f32,
+}
+
+impl Default for ControllerGroundSettings {
+    fn default() -> Self {
+        ControllerGroundSettings {
+            probe_distance: 1.0,
+            grounded_distance: 0.1,
+            stride: 1.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerWallSettings {
+    /// Distance to the side searched for walls
+    pub probe_distance: f32,
+    /// Largest tilt of a wall away from vertical that can be run on, in radians
+    pub max_angle: f32,
+    /// Speed along the wall needed to start and keep a wall run
+    pub min_speed: f32,
+    /// Seconds a wall run lasts before the character falls off
+    pub duration: f32,
+    /// Fraction of gravity applied while wall running
+    pub gravity_scale: f32,
+    /// Speed given along the wall normal by a wall jump
+    pub jump_push: f32,
+}
+
+impl Default for ControllerWallSettings {
+    fn default() -> Self {
+        ControllerWallSettings {
+            probe_distance: 0.5,
+            max_angle: 15.0_f32.to_radians(),
+            min_speed: 6.0,
+            duration: 1.5,
+            gravity_scale: 0.25,
+            jump_push: 8.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerLedgeSettings {
+    /// Distance in front of the capsule searched for a ledge
+    pub reach: f32,
+    /// Highest ledge above the feet that can be grabbed
+    pub max_height: f32,
+    /// Seconds a mantle takes from hanging to standing on the ledge
+    pub duration: f32,
+    /// Seconds after letting go before a ledge can be grabbed again
+    pub cooldown: f32,
+}
+
+impl Default for ControllerLedgeSettings {
+    fn default() -> Self {
+        ControllerLedgeSettings {
+            reach: 0.5,
+            max_height: 4.0,
+            duration: 0.4,
+            cooldown: 0.3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerSwimSettings {
+    /// Fraction of the capsule below a fluid surface at which swimming starts
+    pub submerged: f32,
+    /// Rate at which swimming input reaches the target speed, in units/s²
+    pub acceleration: f32,
+}
+
+impl Default for ControllerSwimSettings {
+    fn default() -> Self {
+        ControllerSwimSettings {
+            submerged: 0.5,
+            acceleration: 30.0,
+        }
+    }
+}
+
+/// Charges, cooldown and recharge rules shared by movement abilities
+#[derive(Debug, Clone, Copy)]
+pub struct AbilityChargeSettings {
+    /// Uses available before the ability has to recharge
+    pub charges: u32,
+    /// Seconds after a use before the ability can be used again
+    pub cooldown: f32,
+    /// Seconds to regain one charge, `f32::INFINITY` never recharges over time
+    pub recharge: f32,
+    /// Every charge comes back when standing on the ground
+    pub refill_on_land: bool,
+}
+
+impl Default for AbilityChargeSettings {
+    fn default() -> Self {
+        AbilityChargeSettings {
+            charges: 1,
+            cooldown: 0.0,
+            recharge: f32::INFINITY,
+            refill_on_land: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerDashSettings {
+    /// Speed along the input direction during a dash
+    pub speed: f32,
+    /// Seconds a dash lasts, gravity is ignored meanwhile
+    pub duration: f32,
+    /// Speed the character keeps once a dash ends
+    pub exit_speed: f32,
+    pub charges: AbilityChargeSettings,
+}
+
+impl Default for ControllerDashSettings {
+    fn default() -> Self {
+        ControllerDashSettings {
+            speed: 30.0,
+            duration: 0.2,
+            exit_speed: 10.0,
+            charges: AbilityChargeSettings {
+                cooldown: 0.5,
+                recharge: 2.0,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerAirJumpSettings {
+    /// Upward velocity given by a jump in mid-air
+    pub force: f32,
+    /// Number of air jumps, by default refilled on landing
+    pub charges: AbilityChargeSettings,
+}
+
+impl Default for ControllerAirJumpSettings {
+    fn default() -> Self {
+        ControllerAirJumpSettings {
+            force: 8.0,
+            charges: AbilityChargeSettings::default(),
+        }
+    }
+}
+
+/// Frame of reference used to turn movement input into a world direction
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementBasis {
+    /// Relative to the character's own rotation
+    #[default]
+    Character,
+    /// Relative to the yaw of the active [`Camera`]
+    Camera,
+}
@@ -0,0 +1,42 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// Slows a [`ControllerState::Slide`] down through friction and speeds it up
+/// along the ground slope, capped at the current [`ControllerSpeed`]
+pub fn update_slide_force(
+    mut q: Query<(
+        &mut MovementController,
+        &ControllerState,
+        &ControllerSpeed,
+        &ControllerGround,
+    )>,
+    time: Res<Time>,
+) {
+    for (mut character, state, speed, ground) in q.iter_mut() {
+        if *state != ControllerState::Slide {
+            continue;
+        }
+
+        let settings = *character.slide();
+        let up = character.up();
+        let mut forces = *character.forces();
+        let mut velocity = move_towards(
+            forces.movement(),
+            Vec3::ZERO,
+            settings.friction * ground.friction() * time.delta_seconds(),
+        );
+
+        if ground.grounded() {
+            // Part of gravity pulling along the surface, flattened onto the movement plane
+            let normal = ground.normal();
+            let gravity = character.current_gravity();
+            let downhill = gravity - normal * gravity.dot(normal);
+            let downhill = downhill - up * downhill.dot(up);
+            velocity += downhill * settings.slope_acceleration * time.delta_seconds();
+            velocity = project_on_ground(velocity, normal, up);
+        }
+
+        forces.set_movement(velocity.clamp_length_max(speed.get()));
+        character.set_forces(forces);
+    }
+}
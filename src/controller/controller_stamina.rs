@@ -0,0 +1,171 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// Optional resource spent by sprinting, jumping and dashing, insert it next to a
+/// [`MovementController`] to limit those
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Stamina {
+    /// Largest amount of stamina, also the starting amount
+    pub max: f32,
+    /// Stamina regained per second
+    pub regen_rate: f32,
+    /// Seconds after spending stamina before it starts regenerating
+    pub regen_delay: f32,
+    /// Fraction of [`Self::max`] needed to sprint again after running out
+    pub recover: f32,
+    /// Stamina drained per second in [`ControllerState::Run`]
+    pub sprint_cost: f32,
+    /// Stamina spent by every jump, including wall and air jumps
+    pub jump_cost: f32,
+    /// Stamina spent by a dash
+    pub dash_cost: f32,
+    current: f32,
+    since_spent: f32,
+    exhausted: bool,
+    reported: Option<(f32, bool)>,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Stamina::new(100.0)
+    }
+}
+
+impl Stamina {
+    pub fn new(max: f32) -> Self {
+        Stamina {
+            max,
+            regen_rate: max / 5.0,
+            regen_delay: 1.0,
+            recover: 0.25,
+            sprint_cost: max / 6.0,
+            jump_cost: max / 10.0,
+            dash_cost: max / 4.0,
+            current: max,
+            since_spent: f32::INFINITY,
+            exhausted: false,
+            reported: None,
+        }
+    }
+    pub fn with_regen(mut self, regen_rate: f32, regen_delay: f32) -> Self {
+        self.regen_rate = regen_rate;
+        self.regen_delay = regen_delay;
+        self
+    }
+    pub fn with_costs(mut self, sprint_cost: f32, jump_cost: f32, dash_cost: f32) -> Self {
+        self.sprint_cost = sprint_cost;
+        self.jump_cost = jump_cost;
+        self.dash_cost = dash_cost;
+        self
+    }
+    pub fn with_recover(mut self, recover: f32) -> Self {
+        self.recover = recover;
+        self
+    }
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+    /// Current stamina as a fraction of [`Self::max`], for HUD bars
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0.0 {
+            self.current / self.max
+        } else {
+            0.0
+        }
+    }
+    /// Ran out of stamina and did not regain [`Self::recover`] of it yet
+    pub fn exhausted(&self) -> bool {
+        self.exhausted
+    }
+    /// Seconds since stamina was last spent
+    pub fn since_spent(&self) -> f32 {
+        self.since_spent
+    }
+    pub fn can_sprint(&self) -> bool {
+        !self.exhausted && self.current > 0.0
+    }
+    pub fn can_afford(&self, cost: f32) -> bool {
+        self.current >= cost
+    }
+    /// Takes `amount` away and restarts the regeneration delay
+    pub fn spend(&mut self, amount: f32) {
+        if amount <= 0.0 {
+            return;
+        }
+
+        self.current = (self.current - amount).max(0.0);
+        self.since_spent = 0.0;
+        if self.current <= 0.0 {
+            self.exhausted = true;
+        }
+    }
+    pub fn set_current(&mut self, current: f32) {
+        self.current = current.clamp(0.0, self.max);
+    }
+}
+
+/// Sent whenever the amount of [`Stamina`] changes
+#[derive(Event, Debug, Clone)]
+pub struct StaminaChanged {
+    pub entity: Entity,
+    pub current: f32,
+    pub max: f32,
+}
+
+/// Sent when [`Stamina`] runs out
+#[derive(Event, Debug, Clone)]
+pub struct StaminaDepleted {
+    pub entity: Entity,
+}
+
+/// Sent when [`Stamina`] recovered enough to sprint again
+#[derive(Event, Debug, Clone)]
+pub struct StaminaRecovered {
+    pub entity: Entity,
+}
+
+/// Drains [`Stamina`] while sprinting and regenerates it, exhausted sprinters are sent
+/// back to walking by the [`ControllerStateMachine`]
+pub fn update_stamina(
+    mut q: Query<(Entity, &mut Stamina, &ControllerState)>,
+    mut changed: EventWriter<StaminaChanged>,
+    mut depleted: EventWriter<StaminaDepleted>,
+    mut recovered: EventWriter<StaminaRecovered>,
+    time: Res<Time>,
+) {
+    for (entity, mut stamina, state) in q.iter_mut() {
+        let delta = time.delta_seconds();
+
+        if *state == ControllerState::Run {
+            let cost = stamina.sprint_cost * delta;
+            stamina.spend(cost);
+        } else {
+            stamina.since_spent += delta;
+        }
+
+        if stamina.since_spent >= stamina.regen_delay {
+            let current = stamina.current + stamina.regen_rate * delta;
+            stamina.set_current(current);
+        }
+
+        if stamina.exhausted && stamina.current >= stamina.max * stamina.recover {
+            stamina.exhausted = false;
+        }
+
+        let (previous, was_exhausted) = stamina.reported.unwrap_or((stamina.max, false));
+        if stamina.current != previous {
+            changed.send(StaminaChanged {
+                entity,
+                current: stamina.current,
+                max: stamina.max,
+            });
+        }
+        match (was_exhausted, stamina.exhausted) {
+            (false, true) => depleted.send(StaminaDepleted { entity }),
+            (true, false) => recovered.send(StaminaRecovered { entity }),
+            _ => {}
+        }
+
+        stamina.reported = Some((stamina.current, stamina.exhausted));
+    }
+}
@@ -4,24 +4,41 @@ use bevy_rapier3d::prelude::*;
 use leafwing_input_manager::prelude::*;
 use ControllerState::*;
 
-#[derive(Resource, States, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Per-entity movement state, use `Changed<ControllerState>` to react to transitions
+#[derive(Default, Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ControllerState {
     Run,
     Idle,
+    #[default]
     Walk,
     Slide,
     Crouch,
     Jump,
     Fall,
+    /// Running along a wall with reduced gravity, see [`MovementController::wall`]
+    WallRun,
+    /// Rising after pushing off a wall
+    WallJump,
+    /// Holding onto a ledge, see [`ControllerLedge`]
+    Hang,
+    /// Climbing from a ledge onto its top
+    Mantle,
+    /// Moving along a [`Climbable`] without gravity
+    Climb,
+    /// Floating at the surface of a [`FluidVolume`]
+    Swim,
+    /// Moving freely below the surface of a [`FluidVolume`]
+    Dive,
+    /// Moving at a fixed velocity without gravity, see [`MovementController::dash`]
+    Dash,
+    /// Rising after jumping in mid-air, see [`MovementController::air_jump`]
+    AirJump,
+    /// State added by another plugin through [`ControllerStateAppExt`]
+    Custom(&'static str),
 }
 
-impl Default for ControllerState {
-    fn default() -> Self {
-        return Self::Walk;
-    }
-}
-
-#[derive(Default, Resource, Debug, Clone, Copy, PartialEq, Deref, DerefMut)]
+/// Currently active speed of the entity, picked from [`ControllerSpeedSettings`]
+#[derive(Default, Component, Debug, Clone, Copy, PartialEq, Deref, DerefMut)]
 pub struct ControllerSpeed(pub f32);
 
 impl ControllerSpeed {
@@ -36,77 +53,257 @@ impl ControllerSpeed {
     }
 }
 
+/// Built-in states. Within a source state, transitions are ordered by priority:
+/// leaving the ground beats ground states, and jumping beats everything else.
+/// Swim and Climb can be entered from most states and outrank everything but jumps.
+/// Dashes and air jumps outrank everything, they spend their charge and stamina on entry.
+impl Default for ControllerStateMachine {
+    #[rustfmt::skip]
+    fn default() -> Self {
+        let mut machine = ControllerStateMachine::empty();
+        let crouch_pressed = |c: &StateContext| c.actions.just_pressed(CharacterActions::Crouch);
+        // Standing states wait until a crouched capsule has room to grow
+        let headroom = |c: &StateContext| !c.character.headroom_blocked();
+
+        machine
+            .add_state(Idle, StateHooks::default().with_guard(headroom))
+            .add_state(Walk, StateHooks::default().with_guard(headroom))
+            .add_state(Run, StateHooks::default().with_guard(move |c| c.can_sprint() && headroom(c)))
+            .add_state(Jump, StateHooks::default()
+                .with_guard(|c| c.can_afford_jump())
+                .on_enter(|c| {
+                    c.character.start_jump();
+                    let cost = c.jump_cost();
+                    spend(c, cost);
+                }))
+            .add_state(WallJump, StateHooks::default()
+                .with_guard(|c| c.can_afford_jump() && c.wall.normal().is_some())
+                .on_enter(|c| {
+                    if let Some(normal) = c.wall.normal() {
+                        c.character.start_wall_jump(normal);
+                    }
+                    let cost = c.jump_cost();
+                    spend(c, cost);
+                }))
+            .add_state(WallRun, StateHooks::default().on_enter(|c| c.character.start_wall_run()))
+            .add_state(Slide, StateHooks::default()
+                .on_enter(|c| c.character.start_slide())
+                .on_exit(|c| c.character.end_slide()))
+            .add_state(Mantle, StateHooks::default()
+                .on_enter(|c| c.character.jump_timers_mut().since_pressed = f32::INFINITY))
+            .add_state(Dash, StateHooks::default()
+                .on_enter(|c| {
+                    if let Some(dash) = c.character.dash().copied() {
+                        let direction = c.abilities.start_dash(&dash.charges);
+                        c.character.start_dash(direction);
+                        let cost = c.dash_cost();
+                        spend(c, cost);
+                    }
+                })
+                .on_exit(|c| c.character.end_dash()))
+            .add_state(AirJump, StateHooks::default()
+                .on_enter(|c| {
+                    if let Some(air_jump) = c.character.air_jump().copied() {
+                        c.abilities.start_air_jump(&air_jump.charges);
+                        c.character.start_air_jump();
+                        let cost = c.jump_cost();
+                        spend(c, cost);
+                    }
+                }));
+
+        // Letting go of jump early cuts the rise short
+        for state in [Jump, WallJump, AirJump] {
+            machine.add_state(state, StateHooks::default().on_update(|c| {
+                if c.character.jump_timers().held && !c.actions.pressed(CharacterActions::Jump) {
+                    let multiplier = c.character.jump().release_multiplier;
+                    let mut forces = *c.character.forces();
+                    forces.set_gravity(forces.gravity() * multiplier);
+                    c.character.set_forces(forces);
+                    c.character.jump_timers_mut().held = false;
+                }
+            }));
+        }
+
+        for state in [Idle, Walk, Run, Slide, Fall] {
+            machine.add_transition(ControllerTransition::new(Jump, |c| c.can_jump()).from(state).with_priority(50));
+        }
+        let dash_pressed = |c: &StateContext| c.actions.just_pressed(CharacterActions::Dash) && c.can_dash();
+        for state in [Idle, Walk, Run, Crouch, Slide, Jump, Fall, WallJump, AirJump] {
+            machine.add_transition(ControllerTransition::new(Dash, dash_pressed).from(state).with_priority(60));
+        }
+        // A buffered jump in the air that cannot start a regular jump, also chaining air jumps
+        let air_jump = |c: &StateContext| c.character.jump_buffered() && !c.character.can_jump() && c.can_air_jump();
+        machine.add_transition(ControllerTransition::new(AirJump, air_jump)
+            .from(Jump).from(Fall).from(WallJump).from(Dash).from(AirJump).with_priority(55).with_reentry());
+        for state in [Idle, Walk, Run, Slide, Crouch] {
+            machine.add_transition(ControllerTransition::new(Fall, |c| !c.grounded()).from(state).with_priority(40));
+        }
+        for state in [Idle, Walk, Run] {
+            machine.add_transition(ControllerTransition::new(Idle, |c| !c.moving()).from(state).with_priority(30));
+        }
+
+        // Landing, or standing up from a crouch
+        let landed = |c: &StateContext| match c.state {
+            Fall => c.grounded(),
+            _ => !c.actions.pressed(CharacterActions::Crouch),
+        };
+        for state in [Fall, Crouch] {
+            machine
+                .add_transition(ControllerTransition::new(Idle, landed).from(state).with_priority(10))
+                .add_transition(ControllerTransition::new(Walk, move |c| landed(c) && c.moving()).from(state).with_priority(11))
+                .add_transition(ControllerTransition::new(Run, move |c| landed(c) && c.moving() && c.wants_sprint()).from(state).with_priority(12));
+        }
+        machine.add_transition(ControllerTransition::new(Crouch, |c| c.grounded() && c.character.headroom_blocked()).from(Fall).with_priority(13));
+
+        machine
+            .add_transition(ControllerTransition::new(Walk, |c| c.moving()).from(Idle).with_priority(20))
+            .add_transition(ControllerTransition::new(Crouch, crouch_pressed).from(Idle).with_priority(10))
+            .add_transition(ControllerTransition::new(Run, |c| c.wants_sprint()).from(Walk).with_priority(10))
+            .add_transition(ControllerTransition::new(Crouch, crouch_pressed).from(Walk).with_priority(20))
+            .add_transition(ControllerTransition::new(Walk, |c| {
+                c.actions.just_released(CharacterActions::Sprint)
+                    || !c.can_sprint()
+                    || c.partial_tilt() && !c.actions.pressed(CharacterActions::Sprint)
+            }).from(Run).with_priority(10))
+            .add_transition(ControllerTransition::new(Crouch, crouch_pressed).from(Run).with_priority(19))
+            .add_transition(ControllerTransition::new(Slide, move |c| crouch_pressed(c) && c.character.can_slide()).from(Run).with_priority(20))
+            .add_transition(ControllerTransition::new(Crouch, |c| {
+                c.character.forces().movement().length() < c.character.slide().min_speed
+            }).from(Slide).with_priority(10))
+            .add_transition(ControllerTransition::new(Walk, |c| c.actions.just_released(CharacterActions::Crouch)).from(Slide).with_priority(19))
+            .add_transition(ControllerTransition::new(Run, |c| c.actions.just_released(CharacterActions::Crouch)).from(Slide).with_priority(20))
+            .add_transition(ControllerTransition::new(Fall, |c| c.character.forces().gravity().dot(c.character.up()) <= 0.0)
+                .from(Jump).from(WallJump).from(AirJump).with_priority(10))
+            .add_transition(ControllerTransition::new(Hang, |c| c.ledge.can_grab())
+                .from(Jump).from(WallJump).from(AirJump).from(Fall).with_priority(30))
+            .add_transition(ControllerTransition::new(WallRun, |c| c.wall.can_run(c.character)).from(Fall).with_priority(20))
+            .add_transition(ControllerTransition::new(Fall, |c| !c.wall.can_run(c.character)).from(WallRun).with_priority(10))
+            .add_transition(ControllerTransition::new(WallJump, |c| c.character.jump_buffered()).from(WallRun).with_priority(20))
+            .add_transition(ControllerTransition::new(Mantle, |c| c.input().z < -0.5 || c.character.jump_buffered()).from(Hang).with_priority(10))
+            .add_transition(ControllerTransition::new(Fall, crouch_pressed).from(Hang).with_priority(20))
+            .add_transition(ControllerTransition::new(Fall, |c| c.ledge.mantle_progress() >= 1.0).from(Mantle).with_priority(10))
+            .add_transition(ControllerTransition::new(Fall, |c| c.climb.entity().is_none()).from(Climb).with_priority(10))
+            .add_transition(ControllerTransition::new(Idle, |c| c.grounded() && c.input().z > 0.5).from(Climb).with_priority(20))
+            .add_transition(ControllerTransition::new(Fall, crouch_pressed).from(Climb).with_priority(30))
+            .add_transition(ControllerTransition::new(Jump, |c| c.character.jump_buffered()).from(Climb).with_priority(50)
+                .with_action(|c| {
+                    let mut forces = *c.character.forces();
+                    forces.set_movement(forces.movement() + c.climb.push());
+                    c.character.set_forces(forces);
+                }))
+            .add_transition(ControllerTransition::new(Dive, |c| c.fluid.underwater()).from(Swim).with_priority(10))
+            .add_transition(ControllerTransition::new(Fall, |c| c.fluid.submerged() < c.character.swim().submerged).from(Swim).with_priority(20))
+            .add_transition(ControllerTransition::new(Jump, |c| c.character.jump_buffered() && !c.fluid.underwater()).from(Swim).with_priority(50))
+            .add_transition(ControllerTransition::new(Swim, |c| !c.fluid.underwater()).from(Dive).with_priority(10))
+            .add_transition(ControllerTransition::new(Fall, |c| {
+                c.abilities.dash_time() >= c.character.dash().map_or(0.0, |dash| dash.duration)
+            }).from(Dash).with_priority(10));
+
+        // Deep enough in a fluid to float, jumps leave the water before swimming again
+        machine.add_transition(ControllerTransition::new(Swim, |c| {
+            c.fluid.submerged() >= c.character.swim().submerged
+                && !matches!(c.state, Dive | Jump | WallJump | AirJump | Hang | Mantle | Climb)
+        }).with_priority(45));
+
+        // Entering a climbable, or walking into one again after stepping off it
+        machine.add_transition(ControllerTransition::new(Climb, |c| {
+            let reattach = c.grounded() && c.input().z < -0.5 && c.climb.entity().is_some();
+            (c.climb.entered() || reattach) && !matches!(c.state, Hang | Mantle)
+        }).with_priority(48));
+
+        machine
+    }
+}
+
+fn spend(context: &mut StateContext, cost: f32) {
+    if let Some(stamina) = context.stamina.as_mut() {
+        stamina.spend(cost);
+    }
+}
+
+/// Advances jump buffering, coyote time and the slide cooldown
+pub fn update_controller_timers(
+    mut q: Query<(&mut MovementController, &ControllerInput)>,
+    time: Res<Time>,
+) {
+    for (mut character, actions) in q.iter_mut() {
+        update_jump_timers(&mut character, actions, time.delta_seconds());
+        let slide_cooldown = (character.slide_cooldown() - time.delta_seconds()).max(0.0);
+        character.set_slide_cooldown(slide_cooldown);
+    }
+}
+
+/// Runs the [`ControllerStateMachine`] for every character. States set directly by
+/// other systems get their exit and enter hooks here before any transition is checked.
 #[rustfmt::skip]
 pub fn update_player_state(
     mut q: Query<(
+        Entity,
         &mut MovementController,
         &KinematicCharacterControllerOutput,
-        &ActionState<CharacterActions>,
+        &ControllerInput,
+        &ActionState<CharacterMovement>,
+        &ControllerWall,
+        &ControllerLedge,
+        &ControllerClimb,
+        &ControllerFluid,
+        &mut ControllerAbilities,
+        Option<&mut Stamina>,
+        &mut ControllerState,
+        &mut ControllerStateHistory,
     )>,
-    state: ResMut<State<ControllerState>>,
-    mut next_state: ResMut<NextState<ControllerState>>,
+    machine: Res<ControllerStateMachine>,
+    mut events: ControllerStateEvents,
+    time: Res<Time>,
 ) {
-    let (mut character, physics, actions) = q.single_mut();
-    let mut new_state = None;
-    let grounded = character.grounded();
+    for (entity, mut character, physics, actions, movement, wall, ledge, climb, fluid, mut abilities, mut stamina, mut state, mut history) in q.iter_mut() {
+        let delta = time.delta_seconds();
+        let mut context = StateContext {
+            entity,
+            state: history.applied(),
+            previous: history.previous(),
+            time_in_state: history.time_in_state() + delta,
+            delta,
+            character: &mut character,
+            physics,
+            actions,
+            movement,
+            wall,
+            ledge,
+            climb,
+            fluid,
+            abilities: &mut abilities,
+            stamina: stamina.as_deref_mut(),
+        };
 
-    match state.get() {
-        Run => {
-            if actions.just_released(CharacterActions::Sprint) { new_state = Some(Walk) }
-            if actions.just_pressed(CharacterActions::Crouch) { new_state = Some(Slide) }
-            if actions.just_pressed(CharacterActions::Jump) { new_state = Some(Jump) }
-            if physics.effective_translation == Vec3::ZERO { new_state = Some(Idle) }
-            if !grounded { new_state = Some(Fall) }
+        if context.state != *state {
+            let from = context.state;
+            machine.change(&mut context, *state, None);
+            events.send(&context, from);
         }
-        Walk => {
-            if actions.pressed(CharacterActions::Sprint) { new_state = Some(Run) }
-            if actions.just_pressed(CharacterActions::Crouch) { new_state = Some(Crouch) }
-            if actions.just_pressed(CharacterActions::Jump) { new_state = Some(Jump) }
-            if physics.effective_translation == Vec3::ZERO { new_state = Some(Idle) }
-            if !grounded { new_state = Some(Fall) }
-        }
-        Slide => {
-            if actions.just_pressed(CharacterActions::Jump) { new_state = Some(Jump) }
-            if actions.just_released(CharacterActions::Crouch) { new_state = Some(Run) }
-            if !grounded { new_state = Some(Fall) }
-        }
-        Jump => {
-            if physics.effective_translation.y < 0.0 { new_state = Some(Fall) }
-        }
-        Idle => {
-            if actions.just_pressed(CharacterActions::Jump) { new_state = Some(Jump) }
-            if actions.just_pressed(CharacterActions::Crouch) { new_state = Some(Crouch) }
-            if physics.effective_translation != Vec3::ZERO { new_state = Some(Walk) }
-            if !grounded { new_state = Some(Fall) }
-        }
-        Crouch => {
-            if actions.just_released(CharacterActions::Crouch) { 
-                new_state = Some(Idle);
-                if physics.effective_translation != Vec3::ZERO { 
-                    new_state = Some(Walk);
-                    if actions.pressed(CharacterActions::Sprint) { 
-                        new_state = Some(Run) 
-                    } 
-                } 
-            }
-            if !grounded { new_state = Some(Fall) }
-        }
-        Fall => {
-            if grounded { 
-                new_state = Some(Idle);
-                if physics.effective_translation != Vec3::ZERO { 
-                    new_state = Some(Walk);
-                    if actions.pressed(CharacterActions::Sprint) { 
-                        new_state = Some(Run) 
-                    } 
-                } 
-            }
+        let from = context.state;
+        if machine.step(&mut context).is_some() {
+            events.send(&context, from);
         }
+
+        history.record(&context);
+        state.set_if_neq(context.state);
     }
+}
 
-    character.set_grounded(physics.grounded);
+fn update_jump_timers(character: &mut MovementController, actions: &ControllerInput, delta: f32) {
+    let grounded = character.grounded();
+    let timers = character.jump_timers_mut();
+
+    if grounded {
+        timers.since_grounded = 0.0;
+    } else {
+        timers.since_grounded += delta;
+    }
 
-    if let Some(new_state) = new_state {
-        next_state.set(new_state)
+    if actions.just_pressed(CharacterActions::Jump) {
+        timers.since_pressed = 0.0;
+    } else {
+        timers.since_pressed += delta;
     }
 }
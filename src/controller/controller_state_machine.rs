@@ -0,0 +1,388 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Predicate over a [`StateContext`], used by transitions and guards
+pub type StateCondition = Box<dyn Fn(&StateContext) -> bool + Send + Sync>;
+
+/// Side effect run on a [`StateContext`] when entering, leaving or staying in a state
+pub type StateHook = Box<dyn Fn(&mut StateContext) + Send + Sync>;
+
+/// Everything a [`StateCondition`] or [`StateHook`] can read or change about one character
+/// during [`update_player_state`]
+pub struct StateContext<'a> {
+    pub entity: Entity,
+    /// State the character is in, or entering while running enter hooks
+    pub state: ControllerState,
+    /// State the character was in before the current one
+    pub previous: ControllerState,
+    /// Seconds spent in the current state
+    pub time_in_state: f32,
+    /// Length of the current controller step
+    pub delta: f32,
+    pub character: &'a mut MovementController,
+    pub physics: &'a KinematicCharacterControllerOutput,
+    pub actions: &'a ControllerInput,
+    pub movement: &'a ActionState<CharacterMovement>,
+    pub wall: &'a ControllerWall,
+    pub ledge: &'a ControllerLedge,
+    pub climb: &'a ControllerClimb,
+    pub fluid: &'a ControllerFluid,
+    pub abilities: &'a mut ControllerAbilities,
+    pub stamina: Option<&'a mut Stamina>,
+}
+
+impl StateContext<'_> {
+    pub fn grounded(&self) -> bool {
+        self.character.grounded()
+    }
+    /// Local movement input, see [`CharacterMovement::input`]
+    pub fn input(&self) -> Vec3 {
+        CharacterMovement::input(self.movement, self.character.stick())
+    }
+    /// Analog stick tilt between 0 and 1
+    pub fn tilt(&self) -> f32 {
+        CharacterMovement::analog(self.movement, self.character.stick()).length()
+    }
+    /// Stick tilted past [`MovementController::run_threshold`]
+    pub fn full_tilt(&self) -> bool {
+        self.tilt() >= self.character.run_threshold()
+    }
+    /// Stick tilted, but not enough to run
+    pub fn partial_tilt(&self) -> bool {
+        let tilt = self.tilt();
+        tilt > 0.0 && tilt < self.character.run_threshold()
+    }
+    /// Sprint is held or the stick is fully tilted
+    pub fn wants_sprint(&self) -> bool {
+        self.actions.pressed(CharacterActions::Sprint) || self.full_tilt()
+    }
+    /// Character actually moved during the last physics step
+    pub fn moving(&self) -> bool {
+        self.physics.effective_translation != Vec3::ZERO
+    }
+    /// [`Stamina::jump_cost`], zero without [`Stamina`]
+    pub fn jump_cost(&self) -> f32 {
+        self.stamina
+            .as_ref()
+            .map_or(0.0, |stamina| stamina.jump_cost)
+    }
+    /// [`Stamina::dash_cost`], zero without [`Stamina`]
+    pub fn dash_cost(&self) -> f32 {
+        self.stamina
+            .as_ref()
+            .map_or(0.0, |stamina| stamina.dash_cost)
+    }
+    /// Enough [`Stamina`] for a jump, always true without it
+    pub fn can_afford_jump(&self) -> bool {
+        self.can_afford(self.jump_cost())
+    }
+    /// Enough [`Stamina`] to spend `cost`, always true without it
+    pub fn can_afford(&self, cost: f32) -> bool {
+        self.stamina
+            .as_ref()
+            .is_none_or(|stamina| stamina.can_afford(cost))
+    }
+    /// Not exhausted, always true without [`Stamina`]
+    pub fn can_sprint(&self) -> bool {
+        self.stamina
+            .as_ref()
+            .is_none_or(|stamina| stamina.can_sprint())
+    }
+    /// Buffered jump within coyote time, see [`MovementController::can_jump`]
+    pub fn can_jump(&self) -> bool {
+        self.character.can_jump() && self.can_afford_jump()
+    }
+    /// Dash enabled with a charge ready and enough [`Stamina`] for it
+    pub fn can_dash(&self) -> bool {
+        let ready = self
+            .character
+            .dash()
+            .is_some_and(|dash| self.abilities.dash().ready(&dash.charges));
+        ready && self.can_afford(self.dash_cost())
+    }
+    /// Air jumps enabled with a charge ready and enough [`Stamina`] for a jump
+    pub fn can_air_jump(&self) -> bool {
+        let ready = self
+            .character
+            .air_jump()
+            .is_some_and(|air_jump| self.abilities.air_jump().ready(&air_jump.charges));
+        ready && self.can_afford_jump()
+    }
+}
+
+/// Condition under which a character moves from one state to another
+pub struct ControllerTransition {
+    from: Vec<ControllerState>,
+    to: ControllerState,
+    priority: i32,
+    condition: StateCondition,
+    action: Option<StateHook>,
+    reenter: bool,
+}
+
+impl ControllerTransition {
+    /// Transition into `to` whenever `condition` holds, from any state unless
+    /// restricted with [`Self::from`]
+    pub fn new(
+        to: ControllerState,
+        condition: impl Fn(&StateContext) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        ControllerTransition {
+            from: Vec::new(),
+            to,
+            priority: 0,
+            condition: Box::new(condition),
+            action: None,
+            reenter: false,
+        }
+    }
+    /// Only consider the transition in `state`, can be called repeatedly
+    pub fn from(mut self, state: ControllerState) -> Self {
+        self.from.push(state);
+        self
+    }
+    /// Transitions with a higher priority win when several conditions hold,
+    /// ties go to the one registered first
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+    /// Runs between the exit hooks of the old state and the enter hooks of the new one
+    pub fn with_action(
+        mut self,
+        action: impl Fn(&mut StateContext) + Send + Sync + 'static,
+    ) -> Self {
+        self.action = Some(Box::new(action));
+        self
+    }
+    /// Lets the transition fire while already in its target state, running the
+    /// exit and enter hooks again
+    pub fn with_reentry(mut self) -> Self {
+        self.reenter = true;
+        self
+    }
+    /// States the transition starts from, empty for any state
+    pub fn sources(&self) -> &[ControllerState] {
+        &self.from
+    }
+    pub fn to(&self) -> ControllerState {
+        self.to
+    }
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+    fn applies(&self, context: &StateContext) -> bool {
+        (self.from.is_empty() || self.from.contains(&context.state))
+            && (self.to != context.state || self.reenter)
+            && (self.condition)(context)
+    }
+}
+
+/// Guard and hooks of a single state
+#[derive(Default)]
+pub struct StateHooks {
+    guard: Option<StateCondition>,
+    enter: Vec<StateHook>,
+    exit: Vec<StateHook>,
+    update: Vec<StateHook>,
+}
+
+impl StateHooks {
+    /// Blocks every transition into the state while `guard` fails
+    pub fn with_guard(
+        mut self,
+        guard: impl Fn(&StateContext) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.guard = Some(Box::new(guard));
+        self
+    }
+    pub fn on_enter(mut self, hook: impl Fn(&mut StateContext) + Send + Sync + 'static) -> Self {
+        self.enter.push(Box::new(hook));
+        self
+    }
+    pub fn on_exit(mut self, hook: impl Fn(&mut StateContext) + Send + Sync + 'static) -> Self {
+        self.exit.push(Box::new(hook));
+        self
+    }
+    /// Runs every step spent in the state, before its transitions are checked
+    pub fn on_update(mut self, hook: impl Fn(&mut StateContext) + Send + Sync + 'static) -> Self {
+        self.update.push(Box::new(hook));
+        self
+    }
+    fn allows(&self, context: &StateContext) -> bool {
+        self.guard.as_ref().is_none_or(|guard| guard(context))
+    }
+    fn merge(&mut self, other: StateHooks) {
+        if other.guard.is_some() {
+            self.guard = other.guard;
+        }
+        self.enter.extend(other.enter);
+        self.exit.extend(other.exit);
+        self.update.extend(other.update);
+    }
+}
+
+/// Transition table and per-state hooks driving [`ControllerState`].
+/// The default value holds the built-in states, add to it with [`ControllerStateAppExt`].
+#[derive(Resource)]
+pub struct ControllerStateMachine {
+    transitions: Vec<ControllerTransition>,
+    states: HashMap<ControllerState, StateHooks>,
+}
+
+impl ControllerStateMachine {
+    /// Machine without any transitions or hooks
+    pub fn empty() -> Self {
+        ControllerStateMachine {
+            transitions: Vec::new(),
+            states: HashMap::default(),
+        }
+    }
+    pub fn add_transition(&mut self, transition: ControllerTransition) -> &mut Self {
+        self.transitions.push(transition);
+        self
+    }
+    /// Adds a guard and hooks to `state`, a new guard replaces the previous one
+    /// while hooks run after the ones already registered
+    pub fn add_state(&mut self, state: ControllerState, hooks: StateHooks) -> &mut Self {
+        self.states.entry(state).or_default().merge(hooks);
+        self
+    }
+    /// Removes the hooks and guard of `state`
+    pub fn clear_state(&mut self, state: ControllerState) -> &mut Self {
+        self.states.remove(&state);
+        self
+    }
+    /// Keeps only the transitions for which `keep` returns true
+    pub fn retain_transitions(
+        &mut self,
+        keep: impl FnMut(&ControllerTransition) -> bool,
+    ) -> &mut Self {
+        self.transitions.retain(keep);
+        self
+    }
+    pub fn transitions(&self) -> &[ControllerTransition] {
+        &self.transitions
+    }
+
+    /// Highest priority transition that applies in `context`
+    fn next(&self, context: &StateContext) -> Option<&ControllerTransition> {
+        self.transitions
+            .iter()
+            .filter(|transition| transition.applies(context))
+            .filter(|transition| {
+                self.states
+                    .get(&transition.to)
+                    .is_none_or(|hooks| hooks.allows(context))
+            })
+            .fold(
+                None,
+                |best: Option<&ControllerTransition>, transition| match best {
+                    Some(best) if best.priority >= transition.priority => Some(best),
+                    _ => Some(transition),
+                },
+            )
+    }
+
+    fn run(&self, state: ControllerState, context: &mut StateContext, pick: HookKind) {
+        let Some(hooks) = self.states.get(&state) else {
+            return;
+        };
+        let hooks = match pick {
+            HookKind::Enter => &hooks.enter,
+            HookKind::Exit => &hooks.exit,
+            HookKind::Update => &hooks.update,
+        };
+        for hook in hooks {
+            hook(context);
+        }
+    }
+
+    /// Moves `context` from its state into `to`, running exit hooks, the transition
+    /// action and enter hooks in that order
+    pub(crate) fn change(
+        &self,
+        context: &mut StateContext,
+        to: ControllerState,
+        action: Option<&StateHook>,
+    ) {
+        let from = context.state;
+        self.run(from, context, HookKind::Exit);
+        if let Some(action) = action {
+            action(context);
+        }
+
+        context.previous = from;
+        context.state = to;
+        context.time_in_state = 0.0;
+        self.run(to, context, HookKind::Enter);
+    }
+
+    /// Runs update hooks, then takes at most one transition, returns the new state
+    pub(crate) fn step(&self, context: &mut StateContext) -> Option<ControllerState> {
+        self.run(context.state, context, HookKind::Update);
+
+        let transition = self.next(context)?;
+        let to = transition.to;
+        self.change(context, to, transition.action.as_ref());
+        Some(to)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum HookKind {
+    Enter,
+    Exit,
+    Update,
+}
+
+/// Per-character bookkeeping of the [`ControllerStateMachine`]
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct ControllerStateHistory {
+    applied: ControllerState,
+    previous: ControllerState,
+    time_in_state: f32,
+}
+
+impl ControllerStateHistory {
+    /// State the character was in before the current one
+    pub fn previous(&self) -> ControllerState {
+        self.previous
+    }
+    /// Seconds spent in the current state
+    pub fn time_in_state(&self) -> f32 {
+        self.time_in_state
+    }
+    pub(crate) fn applied(&self) -> ControllerState {
+        self.applied
+    }
+    pub(crate) fn record(&mut self, context: &StateContext) {
+        self.applied = context.state;
+        self.previous = context.previous;
+        self.time_in_state = context.time_in_state;
+    }
+}
+
+/// Registers custom controller states and transitions from other plugins
+pub trait ControllerStateAppExt {
+    fn add_controller_transition(&mut self, transition: ControllerTransition) -> &mut Self;
+    fn add_controller_state(&mut self, state: ControllerState, hooks: StateHooks) -> &mut Self;
+}
+
+impl ControllerStateAppExt for App {
+    fn add_controller_transition(&mut self, transition: ControllerTransition) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(ControllerStateMachine::default)
+            .add_transition(transition);
+        self
+    }
+    fn add_controller_state(&mut self, state: ControllerState, hooks: StateHooks) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(ControllerStateMachine::default)
+            .add_state(state, hooks);
+        self
+    }
+}
@@ -0,0 +1,115 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Wall next to the character, probed when [`MovementController::wall`] is enabled
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct ControllerWall {
+    normal: Option<Vec3>,
+    entity: Option<Entity>,
+    run_time: f32,
+}
+
+impl ControllerWall {
+    /// Normal of the wall beside the character, pointing away from it
+    pub fn normal(&self) -> Option<Vec3> {
+        self.normal
+    }
+    pub fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+    /// Seconds spent wall running since the last landing or wall jump
+    pub fn run_time(&self) -> f32 {
+        self.run_time
+    }
+    /// Airborne next to a wall, moving fast enough along it and with wall run time left
+    pub fn can_run(&self, character: &MovementController) -> bool {
+        let (Some(settings), Some(normal)) = (character.wall(), self.normal) else {
+            return false;
+        };
+
+        let up = character.up();
+        let movement = character.forces().movement();
+        let along = movement - normal * movement.dot(normal) - up * movement.dot(up);
+
+        !character.grounded()
+            && self.run_time < settings.duration
+            && along.length() >= settings.min_speed
+    }
+}
+
+/// Casts the capsule to both sides of its movement direction looking for a wall
+pub fn update_wall_probe(
+    mut q: Query<(
+        Entity,
+        &MovementController,
+        &mut ControllerWall,
+        &ControllerState,
+        &Collider,
+        &Transform,
+    )>,
+    rapier: Option<Res<RapierContext>>,
+    time: Res<Time>,
+) {
+    for (entity, character, mut wall, state, collider, transform) in q.iter_mut() {
+        match state {
+            ControllerState::WallRun => wall.run_time += time.delta_seconds(),
+            ControllerState::WallJump => wall.run_time = 0.0,
+            _ if character.grounded() => wall.run_time = 0.0,
+            _ => {}
+        }
+
+        let (Some(settings), Some(rapier)) = (character.wall(), rapier.as_ref()) else {
+            wall.normal = None;
+            wall.entity = None;
+            continue;
+        };
+
+        let up = character.up();
+        let movement = character.forces().movement();
+        let forward = (movement - up * movement.dot(up))
+            .try_normalize()
+            .unwrap_or(transform.forward());
+        let right = forward.cross(up).normalize_or_zero();
+
+        let hit = [right, -right]
+            .into_iter()
+            .filter_map(|side| {
+                // Backing off from the side first still finds a wall the capsule already touches
+                rapier
+                    .cast_shape(
+                        transform.translation - side * PROBE_SKIN,
+                        transform.rotation,
+                        side,
+                        collider,
+                        settings.probe_distance + PROBE_SKIN,
+                        false,
+                        QueryFilter::new()
+                            .exclude_collider(entity)
+                            .exclude_sensors(),
+                    )
+                    .and_then(|(hit, toi)| Some((hit, toi.toi, toi.details?.normal1)))
+            })
+            .filter(|(_, _, normal)| normal.dot(up).abs() <= settings.max_angle.sin())
+            .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b));
+
+        wall.entity = hit.map(|(hit, _, _)| hit);
+        wall.normal = hit.map(|(_, _, normal)| (normal - up * normal.dot(up)).normalize_or_zero());
+    }
+}
+
+/// Keeps a [`ControllerState::WallRun`] from pushing into the wall
+pub fn update_wall_force(
+    mut q: Query<(&mut MovementController, &ControllerWall, &ControllerState)>,
+) {
+    for (mut character, wall, state) in q.iter_mut() {
+        let (ControllerState::WallRun, Some(normal)) = (state, wall.normal()) else {
+            continue;
+        };
+
+        let mut forces = *character.forces();
+        let movement = forces.movement();
+        forces.set_movement(movement - normal * movement.dot(normal).min(0.0));
+        character.set_forces(forces);
+    }
+}
@@ -1,11 +1,46 @@
+#[allow(clippy::module_inception)]
 mod controller;
 mod controller_movement;
 mod controller_state;
 mod controller_actions;
 mod controller_settings;
+mod controller_bundle;
+mod controller_input;
+mod controller_crouch;
+mod controller_slide;
+mod controller_ground;
+mod controller_platform;
+mod controller_wall;
+mod controller_ledge;
+mod controller_climb;
+mod controller_fluid;
+mod controller_abilities;
+mod controller_stamina;
+mod controller_state_machine;
+mod controller_events;
+mod controller_animation;
+mod controller_gizmos;
+mod controller_model;
 
 pub use controller_settings::*;
 pub use controller_actions::*;
 pub use controller::*;
+pub use controller_bundle::*;
 pub use controller_movement::*;
 pub use controller_state::*;
+pub use controller_input::*;
+pub use controller_crouch::*;
+pub use controller_slide::*;
+pub use controller_ground::*;
+pub use controller_platform::*;
+pub use controller_wall::*;
+pub use controller_ledge::*;
+pub use controller_climb::*;
+pub use controller_fluid::*;
+pub use controller_abilities::*;
+pub use controller_stamina::*;
+pub use controller_state_machine::*;
+pub use controller_events::*;
+pub use controller_animation::*;
+pub use controller_gizmos::*;
+pub use controller_model::*;
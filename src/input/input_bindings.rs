@@ -0,0 +1,195 @@
+use super::input_conflicts::shares_input;
+use bevy::input::gamepad::GamepadButton;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use leafwing_input_manager::user_input::InputKind;
+use std::marker::PhantomData;
+
+/// Actions that ship with a default set of bindings
+pub trait DefaultBindings: Actionlike + Copy {
+    fn default_input_map() -> InputMap<Self>;
+}
+
+/// Shared bindings for `A`, entities spawned by boxy copy their [`InputMap`] from here.
+/// Entities still using the previous bindings follow changes to this resource.
+#[derive(Resource, Debug, Clone, PartialEq, Deref, DerefMut)]
+pub struct InputBindings<A: Actionlike>(pub InputMap<A>);
+
+impl<A: DefaultBindings> Default for InputBindings<A> {
+    fn default() -> Self {
+        Self(A::default_input_map())
+    }
+}
+
+impl<A: Actionlike + Copy> InputBindings<A> {
+    /// Actions other than `action` that are already bound to `input`.
+    /// Only `A` is checked, [`InputConflicts`] also looks through the other action types.
+    pub fn conflicts(&self, action: A, input: &UserInput) -> Vec<A> {
+        self.0
+            .iter()
+            .filter(|(bound, inputs)| {
+                **bound != action && inputs.iter().any(|bound| shares_input(bound, input))
+            })
+            .map(|(bound, _)| *bound)
+            .collect()
+    }
+
+    /// Binds `input` to `action`, replacing its previous button of the same device.
+    /// Returns the conflicting actions, which keep their bindings.
+    pub fn rebind(&mut self, action: A, input: UserInput) -> Vec<A> {
+        let conflicts = self.conflicts(action, &input);
+        let device = ButtonDevice::of(&input);
+
+        if let Some(inputs) = self.0.get_mut(action) {
+            inputs.retain(|bound| device.is_none() || ButtonDevice::of(bound) != device);
+        }
+        self.0.insert(input, action);
+
+        conflicts
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ButtonDevice {
+    KeyboardMouse,
+    Gamepad,
+}
+
+impl ButtonDevice {
+    fn of(input: &UserInput) -> Option<Self> {
+        match input {
+            UserInput::Single(InputKind::Keyboard(_) | InputKind::Mouse(_)) => {
+                Some(Self::KeyboardMouse)
+            }
+            UserInput::Single(InputKind::GamepadButton(_)) => Some(Self::Gamepad),
+            _ => None,
+        }
+    }
+}
+
+/// Action waiting for the next pressed button to become its binding
+#[derive(Resource, Debug)]
+pub struct PendingRebind<A: Actionlike> {
+    action: Option<A>,
+}
+
+impl<A: Actionlike> Default for PendingRebind<A> {
+    fn default() -> Self {
+        Self { action: None }
+    }
+}
+
+impl<A: Actionlike + Copy> PendingRebind<A> {
+    /// Bind the next pressed key, mouse or gamepad button to `action`, `Escape` cancels
+    pub fn start(&mut self, action: A) {
+        self.action = Some(action);
+    }
+    pub fn cancel(&mut self) {
+        self.action = None;
+    }
+    pub fn action(&self) -> Option<A> {
+        self.action
+    }
+}
+
+/// Sent once a [`PendingRebind`] received its input
+#[derive(Event, Debug, Clone)]
+pub struct InputRebound<A: Actionlike> {
+    pub action: A,
+    pub input: UserInput,
+    /// Other actions of `A` bound to `input`, see [`InputConflicts`] for other action types
+    pub conflicts: Vec<A>,
+}
+
+/// Shared, rebindable [`InputBindings`] for `A`
+pub struct InputBindingsPlugin<A>(PhantomData<A>);
+
+impl<A> Default for InputBindingsPlugin<A> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A: DefaultBindings> Plugin for InputBindingsPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputBindings<A>>()
+            .init_resource::<PendingRebind<A>>()
+            .add_event::<InputRebound<A>>()
+            .add_systems(
+                PreUpdate,
+                (listen_for_rebind::<A>, sync_bindings::<A>).chain(),
+            );
+    }
+}
+
+fn listen_for_rebind<A: DefaultBindings>(
+    mut pending: ResMut<PendingRebind<A>>,
+    mut bindings: ResMut<InputBindings<A>>,
+    mut rebound: EventWriter<InputRebound<A>>,
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad: Res<Input<GamepadButton>>,
+) {
+    let Some(action) = pending.action() else {
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Escape) {
+        pending.cancel();
+        return;
+    }
+
+    let input: Option<UserInput> = keys
+        .get_just_pressed()
+        .next()
+        .map(|key| (*key).into())
+        .or_else(|| {
+            mouse
+                .get_just_pressed()
+                .next()
+                .map(|button| (*button).into())
+        })
+        .or_else(|| {
+            gamepad
+                .get_just_pressed()
+                .next()
+                .map(|button| button.button_type.into())
+        });
+
+    if let Some(input) = input {
+        let conflicts = bindings.rebind(action, input.clone());
+        pending.cancel();
+        rebound.send(InputRebound {
+            action,
+            input,
+            conflicts,
+        });
+    }
+}
+
+fn sync_bindings<A: DefaultBindings>(
+    bindings: Res<InputBindings<A>>,
+    mut previous: Local<Option<InputMap<A>>>,
+    mut q: Query<&mut InputMap<A>>,
+) {
+    if !bindings.is_changed() {
+        return;
+    }
+
+    if let Some(previous) = previous.as_ref() {
+        for mut input_map in q.iter_mut() {
+            let gamepad = input_map.gamepad();
+            let mut shared = input_map.clone();
+            shared.clear_gamepad();
+
+            if shared == *previous {
+                *input_map = bindings.0.clone();
+                if let Some(gamepad) = gamepad {
+                    input_map.set_gamepad(gamepad);
+                }
+            }
+        }
+    }
+
+    *previous = Some(bindings.0.clone());
+}
@@ -0,0 +1,89 @@
+use crate::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Action of any input map read while playing, to tell apart conflicts across action types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BoundAction {
+    CharacterMovement(CharacterMovement),
+    CharacterActions(CharacterActions),
+    CameraAction(CameraAction),
+}
+
+impl From<CharacterMovement> for BoundAction {
+    fn from(action: CharacterMovement) -> Self {
+        Self::CharacterMovement(action)
+    }
+}
+
+impl From<CharacterActions> for BoundAction {
+    fn from(action: CharacterActions) -> Self {
+        Self::CharacterActions(action)
+    }
+}
+
+impl From<CameraAction> for BoundAction {
+    fn from(action: CameraAction) -> Self {
+        Self::CameraAction(action)
+    }
+}
+
+/// Every [`InputBindings`] read at the same time while playing, to find an input already
+/// used by another action type. Free camera movement is left out, as it only flies the
+/// editor camera while the character is not controlled.
+#[derive(SystemParam)]
+pub struct InputConflicts<'w> {
+    character_movement: Option<Res<'w, InputBindings<CharacterMovement>>>,
+    character_actions: Option<Res<'w, InputBindings<CharacterActions>>>,
+    camera_actions: Option<Res<'w, InputBindings<CameraAction>>>,
+}
+
+impl InputConflicts<'_> {
+    /// Actions of any type other than `action` that already use a button or axis of `input`
+    pub fn conflicts(&self, action: impl Into<BoundAction>, input: &UserInput) -> Vec<BoundAction> {
+        let action = action.into();
+        let mut conflicts = Vec::new();
+        collect(&self.character_movement, action, input, &mut conflicts);
+        collect(&self.character_actions, action, input, &mut conflicts);
+        collect(&self.camera_actions, action, input, &mut conflicts);
+        conflicts
+    }
+}
+
+fn collect<A: Actionlike + Copy + Into<BoundAction>>(
+    bindings: &Option<Res<InputBindings<A>>>,
+    action: BoundAction,
+    input: &UserInput,
+    conflicts: &mut Vec<BoundAction>,
+) {
+    let Some(bindings) = bindings else {
+        return;
+    };
+
+    conflicts.extend(
+        bindings
+            .iter()
+            .filter(|(_, inputs)| inputs.iter().any(|bound| shares_input(bound, input)))
+            .map(|(bound, _)| (*bound).into())
+            .filter(|bound| *bound != action),
+    );
+}
+
+/// Whether both inputs read a common key, button or axis, also inside chords and virtual pads
+pub(crate) fn shares_input(a: &UserInput, b: &UserInput) -> bool {
+    fn overlap<T: PartialEq>(a: &[T], b: &[T]) -> bool {
+        a.iter().any(|item| b.contains(item))
+    }
+
+    let (a, b) = (a.raw_inputs(), b.raw_inputs());
+    overlap(&a.keycodes, &b.keycodes)
+        || overlap(&a.scan_codes, &b.scan_codes)
+        || overlap(&a.mouse_buttons, &b.mouse_buttons)
+        || overlap(&a.mouse_wheel, &b.mouse_wheel)
+        || overlap(&a.mouse_motion, &b.mouse_motion)
+        || overlap(&a.gamepad_buttons, &b.gamepad_buttons)
+        || a.axis_data
+            .iter()
+            .any(|(axis, _)| b.axis_data.iter().any(|(other, _)| axis == other))
+}
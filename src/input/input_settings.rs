@@ -0,0 +1,92 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+
+/// Every boxy input map, as stored in a settings file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputSettings {
+    pub character_movement: InputMap<CharacterMovement>,
+    pub character_actions: InputMap<CharacterActions>,
+    pub camera_actions: InputMap<CameraAction>,
+    pub camera_movement: InputMap<CameraMovement>,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        Self {
+            character_movement: CharacterMovement::default_input_map(),
+            character_actions: CharacterActions::default_input_map(),
+            camera_actions: CameraAction::default_input_map(),
+            camera_movement: CameraMovement::default_input_map(),
+        }
+    }
+}
+
+impl InputSettings {
+    /// Current [`InputBindings`] of the world, falling back to defaults for missing ones
+    pub fn capture(world: &World) -> Self {
+        fn get<A: DefaultBindings>(world: &World) -> InputMap<A> {
+            world
+                .get_resource::<InputBindings<A>>()
+                .map(|bindings| bindings.0.clone())
+                .unwrap_or_else(A::default_input_map)
+        }
+
+        Self {
+            character_movement: get(world),
+            character_actions: get(world),
+            camera_actions: get(world),
+            camera_movement: get(world),
+        }
+    }
+
+    /// Replaces the world's [`InputBindings`] with these settings
+    pub fn apply(self, world: &mut World) {
+        world.insert_resource(InputBindings(self.character_movement));
+        world.insert_resource(InputBindings(self.character_actions));
+        world.insert_resource(InputBindings(self.camera_actions));
+        world.insert_resource(InputBindings(self.camera_movement));
+    }
+
+    pub fn from_ron(ron: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(ron::from_str(ron)?)
+    }
+    pub fn to_ron(&self) -> Result<String, Box<dyn Error>> {
+        Ok(ron::ser::to_string_pretty(self, Default::default())?)
+    }
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Reads a `.json` file as JSON and anything else as RON
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let contents = std::fs::read_to_string(path.as_ref())?;
+        if is_json(path.as_ref()) {
+            Self::from_json(&contents)
+        } else {
+            Self::from_ron(&contents)
+        }
+    }
+
+    /// Writes a `.json` file as JSON and anything else as RON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        let contents = if is_json(path.as_ref()) {
+            self.to_json()?
+        } else {
+            self.to_ron()?
+        };
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "json")
+}
@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Radial deadzone and response curve applied to analog stick input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StickResponse {
+    /// Tilt below which the stick reads as centered, in `0.0..1.0`
+    pub deadzone: f32,
+    /// Exponent of the response curve, `1.0` is linear
+    pub exponent: f32,
+}
+
+impl Default for StickResponse {
+    fn default() -> Self {
+        StickResponse {
+            deadzone: 0.15,
+            exponent: 1.0,
+        }
+    }
+}
+
+impl StickResponse {
+    pub fn new(deadzone: f32, exponent: f32) -> Self {
+        Self { deadzone, exponent }
+    }
+
+    /// Rescales `input` so the response starts at the deadzone edge and follows the curve
+    pub fn apply(&self, input: Vec2) -> Vec2 {
+        let length = input.length();
+        // A deadzone covering the whole stick would divide zero by zero at full tilt
+        if length <= self.deadzone || 1.0 - self.deadzone <= f32::EPSILON {
+            return Vec2::ZERO;
+        }
+
+        let tilt = ((length.min(1.0) - self.deadzone) / (1.0 - self.deadzone)).powf(self.exponent);
+        input / length * tilt
+    }
+
+    /// Stick binding without leafwing's own deadzone, so [`StickResponse`] can rescale it
+    pub fn raw(stick: DualAxis) -> DualAxis {
+        stick.with_deadzone(DeadZoneShape::Ellipse {
+            radius_x: 0.0,
+            radius_y: 0.0,
+        })
+    }
+}
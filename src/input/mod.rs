@@ -0,0 +1,9 @@
+mod input_bindings;
+mod input_conflicts;
+mod input_settings;
+mod input_stick;
+
+pub use input_bindings::*;
+pub use input_conflicts::*;
+pub use input_settings::*;
+pub use input_stick::*;
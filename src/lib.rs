@@ -1,3 +1,5 @@
+#![allow(clippy::type_complexity)]
+
 /// Player state structures
 pub mod controller;
 
@@ -13,9 +15,13 @@ pub mod debugger;
 /// physics
 pub mod physics;
 
+/// Shared input helpers
+pub mod input;
+
 /// Boxy prelude
 pub mod prelude {
     pub use crate::camera::*;
     pub use crate::controller::*;
+    pub use crate::input::*;
     pub use crate::physics::*;
 }
@@ -22,9 +22,12 @@ fn main() {
                     ..default()
                 }),
         )
-        .add_plugins(BoxyPhysicsPlugin)
+        .add_plugins(BoxyPhysicsPlugin::default())
         .add_plugins(BoxyCameraPlugin)
-        .add_plugins(BoxyControllerPlugin)
+        .add_plugins(BoxyControllerPlugin {
+            gizmos: true,
+            ..Default::default()
+        })
         .add_systems(Startup, build_map)
         .run()
 }
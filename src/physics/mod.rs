@@ -1,3 +1,6 @@
+#[allow(clippy::module_inception)]
 mod physics;
+mod physics_interpolation;
 
 pub use physics::*;
+pub use physics_interpolation::*;
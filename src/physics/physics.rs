@@ -1,10 +1,160 @@
+use crate::physics::*;
+use bevy::ecs::schedule::ScheduleLabel;
 use bevy::prelude::*;
+use bevy::utils::petgraph::Direction;
 use bevy_rapier3d::prelude::*;
 
-pub struct BoxyPhysicsPlugin;
+pub struct BoxyPhysicsPlugin {
+    /// Draw collider wireframes on startup
+    pub debug_render: bool,
+    /// Key flipping the wireframes at runtime, `None` keeps them as configured.
+    /// Change it later through [`PhysicsDebugRender::toggle_key`].
+    pub debug_toggle_key: Option<KeyCode>,
+    /// Stepping of the simulation, `None` steps once per `FixedUpdate` with [`Self::substeps`],
+    /// following the `Time<Fixed>` timestep even when it changes later
+    pub timestep_mode: Option<TimestepMode>,
+    pub gravity: Vec3,
+    /// Substeps of the default fixed timestep
+    pub substeps: usize,
+    /// Rapier units per world unit, see [`RapierPhysicsPlugin::with_physics_scale`]
+    pub length_scale: f32,
+}
+
+impl Default for BoxyPhysicsPlugin {
+    fn default() -> Self {
+        BoxyPhysicsPlugin {
+            debug_render: cfg!(debug_assertions),
+            debug_toggle_key: cfg!(debug_assertions).then_some(KeyCode::F1),
+            timestep_mode: None,
+            gravity: Vec3::new(0.0, -9.81, 0.0),
+            substeps: 1,
+            length_scale: 1.0,
+        }
+    }
+}
+
 impl Plugin for BoxyPhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
-            .add_plugins(RapierDebugRenderPlugin::default());
+        // Rapier added by the user, e.g. with custom user data, keeps its own configuration
+        // but has to step in between the controller systems
+        if app.world.contains_resource::<RapierContext>() {
+            assert!(
+                has_systems(app, FixedUpdate, PhysicsSet::StepSimulation),
+                "Rapier has to run in `FixedUpdate` along with the character controller, \
+                 add it with `RapierPhysicsPlugin::in_fixed_schedule`"
+            );
+        } else {
+            let dt = app
+                .world
+                .get_resource::<Time<Fixed>>()
+                .map_or(1.0 / 64.0, |time| time.timestep().as_secs_f32());
+            let timestep_mode = self.timestep_mode.unwrap_or(TimestepMode::Fixed {
+                dt,
+                substeps: self.substeps,
+            });
+
+            app.insert_resource(RapierConfiguration {
+                gravity: self.gravity,
+                timestep_mode,
+                ..Default::default()
+            })
+            .add_plugins(
+                RapierPhysicsPlugin::<NoUserData>::default()
+                    .with_physics_scale(self.length_scale)
+                    .in_fixed_schedule(),
+            );
+            if self.timestep_mode.is_none() {
+                app.add_systems(
+                    FixedUpdate,
+                    sync_fixed_timestep.before(PhysicsSet::StepSimulation),
+                );
+            }
+        }
+
+        let debug = self.debug_render || self.debug_toggle_key.is_some();
+        if debug && !app.is_plugin_added::<RapierDebugRenderPlugin>() {
+            app.add_plugins(RapierDebugRenderPlugin {
+                enabled: self.debug_render,
+                ..Default::default()
+            });
+        }
+        app.insert_resource(PhysicsDebugRender {
+            toggle_key: self.debug_toggle_key,
+        })
+        .add_systems(Update, toggle_debug_render);
+
+        app.configure_sets(
+            FixedUpdate,
+            (
+                RenderInterpolationSet::Restore.before(PhysicsSet::SyncBackend),
+                RenderInterpolationSet::Record.after(PhysicsSet::Writeback),
+            ),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                restore_render_interpolation.in_set(RenderInterpolationSet::Restore),
+                record_render_interpolation.in_set(RenderInterpolationSet::Record),
+            ),
+        )
+        .add_systems(
+            Update,
+            apply_render_interpolation.in_set(RenderInterpolationSet::Apply),
+        );
+    }
+}
+
+/// Whether `set` holds any system of the `label` schedule
+fn has_systems(app: &App, label: impl ScheduleLabel, set: impl SystemSet) -> bool {
+    let Some(schedule) = app.get_schedule(label) else {
+        return false;
+    };
+    let graph = schedule.graph();
+    let set = &set as &dyn SystemSet;
+
+    graph
+        .system_sets()
+        .filter(|(_, candidate, _)| *candidate == set)
+        .any(|(id, _, _)| {
+            graph
+                .hierarchy()
+                .graph()
+                .neighbors_directed(id, Direction::Outgoing)
+                .next()
+                .is_some()
+        })
+}
+
+/// Keeps the default fixed Rapier step as long as the `FixedUpdate` timestep
+fn sync_fixed_timestep(mut config: ResMut<RapierConfiguration>, time: Res<Time<Fixed>>) {
+    let timestep = time.timestep().as_secs_f32();
+    if let TimestepMode::Fixed { dt, substeps } = config.timestep_mode {
+        if dt != timestep {
+            config.timestep_mode = TimestepMode::Fixed {
+                dt: timestep,
+                substeps,
+            };
+        }
+    }
+}
+
+/// Runtime settings of Rapier's debug render, added by [`BoxyPhysicsPlugin`]
+#[derive(Resource, Debug, Clone)]
+pub struct PhysicsDebugRender {
+    /// Key flipping the wireframes, needs the debug render to be installed by enabling
+    /// [`BoxyPhysicsPlugin::debug_render`] or [`BoxyPhysicsPlugin::debug_toggle_key`]
+    pub toggle_key: Option<KeyCode>,
+}
+
+fn toggle_debug_render(
+    settings: Res<PhysicsDebugRender>,
+    keys: Option<Res<Input<KeyCode>>>,
+    context: Option<ResMut<DebugRenderContext>>,
+) {
+    let (Some(key), Some(keys), Some(mut context)) = (settings.toggle_key, keys, context) else {
+        return;
+    };
+    if keys.just_pressed(key) {
+        context.enabled = !context.enabled;
     }
 }
@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+/// Stages of [`RenderInterpolation`]
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderInterpolationSet {
+    /// Puts the simulated pose back before the fixed step, runs in `FixedUpdate`
+    Restore,
+    /// Remembers the simulated pose after the fixed step, runs in `FixedUpdate`
+    Record,
+    /// Writes the blended pose for rendering, runs in `Update`
+    Apply,
+}
+
+/// Smooths the rendered transform of an entity simulated in `FixedUpdate` by blending
+/// its last two simulated poses. Moving the entity outside the simulation teleports it.
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct RenderInterpolation {
+    previous: Option<Transform>,
+    current: Option<Transform>,
+    rendered: Option<Transform>,
+}
+
+impl RenderInterpolation {
+    pub fn previous(&self) -> Option<Transform> {
+        self.previous
+    }
+    pub fn current(&self) -> Option<Transform> {
+        self.current
+    }
+    /// Blended pose, `alpha` is the fraction of the next fixed step already elapsed
+    pub fn interpolate(&self, alpha: f32) -> Option<Transform> {
+        let (previous, current) = (self.previous?, self.current?);
+
+        Some(Transform {
+            translation: previous.translation.lerp(current.translation, alpha),
+            rotation: previous.rotation.slerp(current.rotation, alpha),
+            scale: previous.scale.lerp(current.scale, alpha),
+        })
+    }
+
+    fn teleport(&mut self, transform: Transform) {
+        self.previous = Some(transform);
+        self.current = Some(transform);
+        self.rendered = None;
+    }
+}
+
+pub(crate) fn restore_render_interpolation(
+    mut q: Query<(&mut Transform, &mut RenderInterpolation)>,
+) {
+    for (mut transform, mut interpolation) in q.iter_mut() {
+        let Some(rendered) = interpolation.rendered.take() else {
+            continue;
+        };
+
+        match interpolation.current {
+            Some(current) if *transform == rendered => *transform = current,
+            _ => interpolation.teleport(*transform),
+        }
+    }
+}
+
+pub(crate) fn record_render_interpolation(mut q: Query<(&Transform, &mut RenderInterpolation)>) {
+    for (transform, mut interpolation) in q.iter_mut() {
+        interpolation.previous = interpolation.current.or(Some(*transform));
+        interpolation.current = Some(*transform);
+    }
+}
+
+pub(crate) fn apply_render_interpolation(
+    mut q: Query<(&mut Transform, &mut RenderInterpolation)>,
+    time: Res<Time<Fixed>>,
+) {
+    for (mut transform, mut interpolation) in q.iter_mut() {
+        if interpolation
+            .rendered
+            .is_some_and(|rendered| rendered != *transform)
+        {
+            interpolation.teleport(*transform);
+            continue;
+        }
+
+        if let Some(blended) = interpolation.interpolate(time.overstep_percentage()) {
+            *transform = blended;
+            interpolation.rendered = Some(blended);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use bevy::prelude::*;
+use boxy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// States entered and ability events sent, in order
+#[derive(Resource, Default)]
+struct Entered {
+    states: Vec<ControllerState>,
+    dashes: usize,
+    air_jumps: Vec<u32>,
+}
+
+fn record(
+    mut changes: EventReader<ControllerStateChanged>,
+    mut dashed: EventReader<Dashed>,
+    mut air_jumped: EventReader<AirJumped>,
+    mut entered: ResMut<Entered>,
+) {
+    entered
+        .states
+        .extend(changes.read().map(|change| change.to));
+    entered.dashes += dashed.read().count();
+    entered
+        .air_jumps
+        .extend(air_jumped.read().map(|jump| jump.remaining));
+}
+
+fn app(builder: MovementControllerBuilder) -> (App, Entity) {
+    let mut app = common::app();
+    app.init_resource::<Entered>()
+        .add_systems(FixedUpdate, record.after(ControllerSet::State));
+    common::spawn_ground(&mut app);
+    let character = common::spawn_character(&mut app, builder);
+    common::run(&mut app, 30);
+    app.world.resource_mut::<Entered>().states.clear();
+    (app, character)
+}
+
+fn press(app: &mut App, key: KeyCode, frames: usize) {
+    app.send_input(key);
+    common::run(app, 3);
+    app.release_input(key);
+    common::run(app, frames);
+}
+
+#[test]
+fn dash_spends_a_charge_on_entry() {
+    let mut dash = ControllerDashSettings::default();
+    dash.charges.refill_on_land = false;
+    let (mut app, character) = app(MovementController::builder().with_dash(dash));
+
+    press(&mut app, KeyCode::Q, 30);
+    let entered = app.world.resource::<Entered>();
+    assert_eq!(entered.states.first(), Some(&ControllerState::Dash));
+    assert_eq!(entered.dashes, 1);
+    let abilities = app.world.get::<ControllerAbilities>(character).unwrap();
+    assert_eq!(abilities.dash().available(&dash.charges), 0);
+}
+
+#[test]
+fn dash_guard_keeps_the_character_out_of_dash() {
+    let dash = ControllerDashSettings::default();
+    let (mut app, character) = app(MovementController::builder().with_dash(dash));
+    app.add_controller_state(
+        ControllerState::Dash,
+        StateHooks::default().with_guard(|_| false),
+    );
+
+    press(&mut app, KeyCode::Q, 30);
+    let entered = app.world.resource::<Entered>();
+    assert!(!entered.states.contains(&ControllerState::Dash));
+    assert_eq!(entered.dashes, 0);
+    let abilities = app.world.get::<ControllerAbilities>(character).unwrap();
+    assert_eq!(abilities.dash().available(&dash.charges), 1);
+}
+
+#[test]
+fn air_jumps_chain_while_rising() {
+    let air_jump = ControllerAirJumpSettings {
+        charges: AbilityChargeSettings {
+            charges: 2,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (mut app, _) = app(MovementController::builder().with_air_jumps(air_jump));
+
+    press(&mut app, KeyCode::Space, 10);
+    press(&mut app, KeyCode::Space, 5);
+    press(&mut app, KeyCode::Space, 5);
+    let entered = app.world.resource::<Entered>();
+    assert_eq!(
+        entered.states,
+        [
+            ControllerState::Jump,
+            ControllerState::AirJump,
+            ControllerState::AirJump
+        ]
+    );
+    assert_eq!(entered.air_jumps, [1, 0]);
+}
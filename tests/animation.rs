@@ -0,0 +1,111 @@
+mod common;
+
+use bevy::prelude::*;
+use boxy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+fn jump(app: &mut App) {
+    app.send_input(KeyCode::Space);
+    common::run(app, 5);
+    app.release_input(KeyCode::Space);
+    common::run(app, 120);
+}
+
+fn crouch(app: &mut App) {
+    app.send_input(KeyCode::ControlLeft);
+    common::run(app, 30);
+    app.release_input(KeyCode::ControlLeft);
+    common::run(app, 30);
+}
+
+#[test]
+fn default_animations_restart_a_once_clip_when_reentering_its_state() {
+    let mut app = common::app();
+    common::spawn_ground(&mut app);
+    let clip = Handle::<AnimationClip>::weak_from_u128(2);
+    let character = common::spawn_character(&mut app, MovementController::builder());
+    app.world.entity_mut(character).insert(
+        CharacterAnimations::default().with_clip_once(ControllerState::Crouch, clip.clone()),
+    );
+    let player = app.world.spawn(AnimationPlayer::default()).id();
+    app.world.entity_mut(character).add_child(player);
+    common::run(&mut app, 30);
+
+    crouch(&mut app);
+    let animations = app.world.get::<CharacterAnimations>(character).unwrap();
+    assert_eq!(animations.playing(), Some(&clip));
+
+    app.world
+        .get_mut::<AnimationPlayer>(player)
+        .unwrap()
+        .seek_to(0.5);
+    crouch(&mut app);
+    assert_eq!(
+        app.world
+            .get::<AnimationPlayer>(player)
+            .unwrap()
+            .seek_time(),
+        0.0
+    );
+}
+
+#[test]
+fn clip_played_once_restarts_on_every_entry() {
+    let mut app = common::app();
+    common::spawn_ground(&mut app);
+    let clip = Handle::<AnimationClip>::weak_from_u128(1);
+    let character = common::spawn_character(&mut app, MovementController::builder());
+    app.world
+        .entity_mut(character)
+        .insert(CharacterAnimations::new().with_clip_once(ControllerState::Jump, clip.clone()));
+    let player = app.world.spawn(AnimationPlayer::default()).id();
+    app.world.entity_mut(character).add_child(player);
+    common::run(&mut app, 30);
+
+    jump(&mut app);
+    let animations = app.world.get::<CharacterAnimations>(character).unwrap();
+    assert_eq!(animations.playing(), Some(&clip));
+    assert_eq!(
+        app.world.get::<ControllerState>(character),
+        Some(&ControllerState::Idle)
+    );
+
+    // Landing has no clip of its own, so the jump clip stays on its last frame
+    app.world
+        .get_mut::<AnimationPlayer>(player)
+        .unwrap()
+        .seek_to(0.5);
+    jump(&mut app);
+    assert_eq!(
+        app.world
+            .get::<AnimationPlayer>(player)
+            .unwrap()
+            .seek_time(),
+        0.0
+    );
+}
+
+#[test]
+fn no_lean_without_gravity() {
+    let mut app = common::app();
+    let character = app
+        .world
+        .spawn(
+            MovementController::builder()
+                .with_gravity(Vec3::ZERO)
+                .with_face_movement(6.0)
+                .build(),
+        )
+        .id();
+    app.send_input(KeyCode::W);
+    common::run(&mut app, 30);
+    app.send_input(KeyCode::D);
+    common::run(&mut app, 30);
+
+    let params = app
+        .world
+        .get::<CharacterAnimationParams>(character)
+        .unwrap();
+    assert!(params.turn_rate() != 0.0);
+    assert_eq!(params.lean(), 0.0);
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use boxy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+fn conflicts(app: &mut App, action: impl Into<BoundAction>, input: KeyCode) -> Vec<BoundAction> {
+    let mut state = SystemState::<InputConflicts>::new(&mut app.world);
+    state
+        .get(&app.world)
+        .conflicts(action, &UserInput::from(input))
+}
+
+#[test]
+fn conflicts_are_found_across_action_types() {
+    let mut app = common::app();
+    app.add_plugins(BoxyCameraPlugin);
+    app.update();
+
+    assert_eq!(
+        conflicts(&mut app, CharacterActions::Jump, KeyCode::W),
+        [BoundAction::CharacterMovement(CharacterMovement::Forward)]
+    );
+    assert_eq!(
+        conflicts(&mut app, CharacterActions::Dash, KeyCode::C),
+        [BoundAction::CameraAction(CameraAction::ModeCycleTrigger)]
+    );
+    assert!(conflicts(&mut app, CharacterActions::Jump, KeyCode::Space).is_empty());
+    assert!(conflicts(&mut app, CharacterActions::Jump, KeyCode::J).is_empty());
+}
@@ -0,0 +1,114 @@
+#![allow(dead_code)]
+
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent, GamepadEvent, GamepadInfo};
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+use bevy::scene::ScenePlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier3d::prelude::*;
+use boxy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use std::time::Duration;
+
+/// Headless app with physics and the controller, without a default player
+pub fn app() -> App {
+    let mut app = headless(60.0);
+    app.add_plugins(physics()).add_plugins(controller());
+    app
+}
+
+/// Headless app stepping frames at `fps`, without physics or the controller
+pub fn headless(fps: f32) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        TransformPlugin,
+        InputPlugin,
+        HierarchyPlugin,
+        AssetPlugin::default(),
+        ScenePlugin,
+    ))
+    .init_resource::<Assets<Mesh>>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        1.0 / fps,
+    )));
+    app
+}
+
+/// Physics without debug rendering
+pub fn physics() -> BoxyPhysicsPlugin {
+    BoxyPhysicsPlugin {
+        debug_render: false,
+        debug_toggle_key: None,
+        ..Default::default()
+    }
+}
+
+/// Controller without a default player
+pub fn controller() -> BoxyControllerPlugin {
+    BoxyControllerPlugin {
+        spawn_player: false,
+        player_model: None,
+        ..Default::default()
+    }
+}
+
+/// Wide static floor with its top at `y = 0`
+pub fn spawn_ground(app: &mut App) -> Entity {
+    app.world
+        .spawn((
+            Collider::cuboid(100.0, 1.0, 100.0),
+            TransformBundle::from_transform(Transform::from_xyz(0.0, -1.0, 0.0)),
+        ))
+        .id()
+}
+
+/// Character standing on the ground spawned by [`spawn_ground`]
+pub fn spawn_character(app: &mut App, builder: MovementControllerBuilder) -> Entity {
+    app.world
+        .spawn(
+            builder
+                .with_transform(Transform::from_xyz(0.0, 1.5, 0.0))
+                .build(),
+        )
+        .id()
+}
+
+/// Ceiling over `z` in `-10..-5`, low enough for a crouched character only
+pub fn spawn_ceiling(app: &mut App) -> Entity {
+    app.world
+        .spawn((
+            Collider::cuboid(5.0, 0.5, 2.5),
+            TransformBundle::from_transform(Transform::from_xyz(0.0, 3.7, -7.5)),
+        ))
+        .id()
+}
+
+/// Crouch-walks forward under the [`spawn_ceiling`] ceiling, then lets go of crouch
+pub fn crouch_under_ceiling(app: &mut App, character: Entity) {
+    app.send_input(KeyCode::ControlLeft);
+    app.send_input(KeyCode::W);
+    while app.world.get::<Transform>(character).unwrap().translation.z > -7.5 {
+        app.update();
+    }
+    app.release_input(KeyCode::ControlLeft);
+    app.release_input(KeyCode::W);
+}
+
+/// Connects the gamepad [`leafwing_input_manager`] sends mocked stick input to
+pub fn connect_gamepad(app: &mut App) {
+    app.world
+        .send_event(GamepadEvent::Connection(GamepadConnectionEvent {
+            gamepad: Gamepad { id: 0 },
+            connection: GamepadConnection::Connected(GamepadInfo {
+                name: "Test gamepad".into(),
+            }),
+        }));
+    app.update();
+}
+
+pub fn run(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
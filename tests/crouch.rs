@@ -0,0 +1,34 @@
+mod common;
+
+use bevy::prelude::*;
+use boxy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+fn state(app: &App, character: Entity) -> ControllerState {
+    *app.world.get::<ControllerState>(character).unwrap()
+}
+
+#[test]
+fn stays_crouched_under_a_ceiling() {
+    let mut app = common::app();
+    common::spawn_ground(&mut app);
+    common::spawn_ceiling(&mut app);
+    let character = common::spawn_character(&mut app, MovementController::builder());
+    common::run(&mut app, 30);
+
+    common::crouch_under_ceiling(&mut app, character);
+    common::run(&mut app, 30);
+
+    let controller = app.world.get::<MovementController>(character).unwrap();
+    assert_eq!(state(&app, character), ControllerState::Crouch);
+    assert!(controller.crouched());
+    assert!(controller.headroom_blocked());
+
+    // Stands up on its own once out from under the ceiling
+    app.send_input(KeyCode::S);
+    common::run(&mut app, 180);
+    let controller = app.world.get::<MovementController>(character).unwrap();
+    assert_eq!(state(&app, character), ControllerState::Walk);
+    assert!(!controller.crouched());
+    assert!(!controller.headroom_blocked());
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use bevy::prelude::*;
+use boxy::prelude::*;
+
+/// [`ControllerStateChanged`] events sent during each controller step
+#[derive(Resource, Default)]
+struct StepChanges(Vec<Vec<(ControllerState, ControllerState)>>);
+
+fn record_changes(
+    mut changes: EventReader<ControllerStateChanged>,
+    mut steps: ResMut<StepChanges>,
+) {
+    let step = changes
+        .read()
+        .map(|change| (change.from, change.to))
+        .collect();
+    steps.0.push(step);
+}
+
+#[test]
+fn crouching_under_a_ceiling_sends_each_change_once() {
+    let mut app = common::app();
+    app.init_resource::<StepChanges>()
+        .add_systems(FixedUpdate, record_changes.after(ControllerSet::State));
+    common::spawn_ground(&mut app);
+    common::spawn_ceiling(&mut app);
+    let character = common::spawn_character(&mut app, MovementController::builder());
+    common::run(&mut app, 30);
+    app.world.resource_mut::<StepChanges>().0.clear();
+
+    common::crouch_under_ceiling(&mut app, character);
+    common::run(&mut app, 60);
+
+    let steps = &app.world.resource::<StepChanges>().0;
+    let repeated = steps.iter().find(|step| step.len() > 1);
+    assert!(
+        repeated.is_none(),
+        "several state changes in one step: {repeated:?}"
+    );
+    let changes: Vec<_> = steps.iter().flatten().copied().collect();
+    assert_eq!(
+        changes,
+        [(ControllerState::Idle, ControllerState::Crouch)],
+        "left the crouch under the ceiling"
+    );
+}
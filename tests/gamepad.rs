@@ -0,0 +1,101 @@
+mod common;
+
+use bevy::prelude::*;
+use boxy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+fn tilt_forward(app: &mut App, tilt: f32) {
+    app.send_input(SingleAxis::from_value(GamepadAxisType::LeftStickY, tilt));
+}
+
+fn planar_speed(app: &App, character: Entity) -> f32 {
+    let velocity = app
+        .world
+        .get::<MovementController>(character)
+        .unwrap()
+        .velocity();
+    velocity.xz().length()
+}
+
+#[test]
+fn partial_tilt_walks_and_full_tilt_runs() {
+    let mut app = common::app();
+    common::spawn_ground(&mut app);
+    let character = common::spawn_character(&mut app, MovementController::builder());
+    common::connect_gamepad(&mut app);
+    common::run(&mut app, 30);
+
+    tilt_forward(&mut app, 0.5);
+    common::run(&mut app, 60);
+    assert_eq!(
+        app.world.get::<ControllerState>(character),
+        Some(&ControllerState::Walk)
+    );
+    let walk = app.world.get::<ControllerSpeed>(character).unwrap().get();
+    let speed = planar_speed(&app, character);
+    assert!(speed > 0.0 && speed < walk, "partial tilt moved at {speed}");
+
+    tilt_forward(&mut app, 1.0);
+    common::run(&mut app, 60);
+    assert_eq!(
+        app.world.get::<ControllerState>(character),
+        Some(&ControllerState::Run)
+    );
+    let run = app.world.get::<ControllerSpeed>(character).unwrap().get();
+    assert!(run > walk);
+    assert!((planar_speed(&app, character) - run).abs() < 0.1);
+}
+
+#[test]
+fn stick_response_curve_shapes_movement() {
+    let speed_at_half_tilt = |exponent: f32| {
+        let mut app = common::app();
+        common::spawn_ground(&mut app);
+        let character = common::spawn_character(&mut app, MovementController::builder());
+        app.world
+            .get_mut::<MovementController>(character)
+            .unwrap()
+            .set_stick(StickResponse::new(0.0, exponent));
+        common::connect_gamepad(&mut app);
+        common::run(&mut app, 30);
+
+        tilt_forward(&mut app, 0.5);
+        common::run(&mut app, 60);
+        let walk = app.world.get::<ControllerSpeed>(character).unwrap().get();
+        planar_speed(&app, character) / walk
+    };
+
+    assert!((speed_at_half_tilt(1.0) - 0.5).abs() < 0.01);
+    assert!((speed_at_half_tilt(2.0) - 0.25).abs() < 0.01);
+}
+
+#[test]
+fn deadzone_covering_the_whole_stick_reads_centered() {
+    let stick = StickResponse::new(1.0, 1.0);
+    assert_eq!(stick.apply(Vec2::Y), Vec2::ZERO);
+    assert_eq!(stick.apply(Vec2::new(0.9, 0.9)), Vec2::ZERO);
+}
+
+#[test]
+fn right_stick_inside_deadzone_does_not_look() {
+    let look = |tilt: f32| {
+        let mut app = common::app();
+        app.add_plugins(BoxyCameraPlugin)
+            .insert_resource(NextState(Some(CameraState::FirstPerson)));
+        common::connect_gamepad(&mut app);
+
+        let mut cameras = app
+            .world
+            .query_filtered::<&Transform, With<boxy::camera::Camera>>();
+        let before = *cameras.single(&app.world);
+        app.send_input(SingleAxis::from_value(GamepadAxisType::RightStickX, tilt));
+        common::run(&mut app, 10);
+        let after = *cameras.single(&app.world);
+        before.rotation.dot(after.rotation).abs()
+    };
+
+    let deadzone = boxy::camera::Camera::default().stick().deadzone;
+    // Quaternions of the same rotation have a dot product of one
+    assert!(look(deadzone * 0.5) > 1.0 - 1e-6);
+    assert!(look(1.0) < 0.999);
+}
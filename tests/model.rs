@@ -0,0 +1,41 @@
+mod common;
+
+use bevy::prelude::*;
+use boxy::prelude::*;
+
+fn model_roots(app: &mut App) -> usize {
+    app.world
+        .query::<&CharacterModelRoot>()
+        .iter(&app.world)
+        .count()
+}
+
+#[test]
+fn scene_model_spawns_without_mesh_assets_and_goes_away_when_removed() {
+    let mut app = common::app();
+    assert!(!app.world.contains_resource::<Assets<StandardMaterial>>());
+    let character = common::spawn_character(&mut app, MovementController::builder());
+    app.world
+        .entity_mut(character)
+        .insert(CharacterModel::Scene {
+            scene: Handle::weak_from_u128(1),
+            offset: Transform::default(),
+        });
+    common::run(&mut app, 2);
+    assert_eq!(model_roots(&mut app), 1);
+
+    app.world.entity_mut(character).remove::<CharacterModel>();
+    common::run(&mut app, 2);
+    assert_eq!(model_roots(&mut app), 0);
+}
+
+#[test]
+fn capsule_model_needs_mesh_assets() {
+    let mut app = common::app();
+    let character = common::spawn_character(&mut app, MovementController::builder());
+    app.world
+        .entity_mut(character)
+        .insert(CharacterModel::default());
+    common::run(&mut app, 2);
+    assert_eq!(model_roots(&mut app), 0);
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use boxy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Distance walked forward in 60 frames
+fn walked(mut app: App) -> f32 {
+    app.add_plugins(common::physics())
+        .add_plugins(common::controller());
+    common::spawn_ground(&mut app);
+    let character = common::spawn_character(&mut app, MovementController::builder());
+    common::run(&mut app, 30);
+
+    let start = app.world.get::<Transform>(character).unwrap().translation;
+    app.send_input(KeyCode::W);
+    common::run(&mut app, 60);
+    let end = app.world.get::<Transform>(character).unwrap().translation;
+    start.distance(end)
+}
+
+#[test]
+fn user_added_rapier_in_fixed_update_walks_as_far() {
+    // Low frame rate, so every frame runs several fixed steps
+    let own = walked(common::headless(30.0));
+    let mut app = common::headless(30.0);
+    app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default().in_fixed_schedule());
+    let user = walked(app);
+
+    assert!(own > 10.0, "walked {own}");
+    assert!((own - user).abs() < 0.1, "walked {user} instead of {own}");
+}
+
+#[test]
+#[should_panic(expected = "in_fixed_schedule")]
+fn user_added_rapier_outside_fixed_update_panics() {
+    let mut app = common::headless(60.0);
+    app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+        .add_plugins(common::physics());
+}
+
+#[test]
+fn default_timestep_follows_fixed_time() {
+    let mut app = common::app();
+    app.world
+        .resource_mut::<Time<Fixed>>()
+        .set_timestep_hz(30.0);
+    let ball = app
+        .world
+        .spawn((
+            RigidBody::Dynamic,
+            Collider::ball(0.5),
+            TransformBundle::from_transform(Transform::from_xyz(0.0, 20.0, 0.0)),
+        ))
+        .id();
+
+    // A second of falling, about half of gravity
+    common::run(&mut app, 60);
+    let fallen = 20.0 - app.world.get::<Transform>(ball).unwrap().translation.y;
+    assert!((4.0..6.0).contains(&fallen), "fell {fallen}");
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use boxy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Kinematic platform moving at a constant velocity and spinning around Y
+#[derive(Component)]
+struct Platform {
+    velocity: Vec3,
+    spin: f32,
+}
+
+fn move_platforms(mut q: Query<(&mut Transform, &Platform)>, time: Res<Time>) {
+    for (mut transform, platform) in q.iter_mut() {
+        transform.translation += platform.velocity * time.delta_seconds();
+        transform.rotate_y(platform.spin * time.delta_seconds());
+    }
+}
+
+fn setup(velocity: Vec3, spin: f32) -> (App, Entity, Entity) {
+    let mut app = common::app();
+    app.add_systems(FixedUpdate, move_platforms.before(ControllerSet::State));
+
+    let platform = app
+        .world
+        .spawn((
+            RigidBody::KinematicPositionBased,
+            Collider::cuboid(6.0, 0.5, 6.0),
+            TransformBundle::from_transform(Transform::from_xyz(0.0, 0.5, 0.0)),
+        ))
+        .id();
+    let character = app
+        .world
+        .spawn(
+            MovementController::builder()
+                .with_transform(Transform::from_xyz(3.0, 3.5, 0.0))
+                .build(),
+        )
+        .id();
+
+    // Settle on the platform before it starts moving
+    common::run(&mut app, 60);
+    app.world
+        .entity_mut(platform)
+        .insert(Platform { velocity, spin });
+    (app, platform, character)
+}
+
+/// Character transform relative to the platform
+fn local(app: &App, platform: Entity, character: Entity) -> Transform {
+    let platform = app.world.get::<Transform>(platform).unwrap();
+    let character = app.world.get::<Transform>(character).unwrap();
+    Transform {
+        translation: platform.rotation.inverse() * (character.translation - platform.translation),
+        rotation: platform.rotation.inverse() * character.rotation,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn rides_a_moving_and_rotating_platform() {
+    let (mut app, platform, character) = setup(Vec3::new(2.0, 0.0, 1.0), 1.0);
+    let before = local(&app, platform, character);
+    assert!(
+        app.world
+            .get::<ControllerPlatform>(character)
+            .unwrap()
+            .entity()
+            == Some(platform)
+    );
+
+    common::run(&mut app, 120);
+    let after = local(&app, platform, character);
+    let moved = app
+        .world
+        .get::<Transform>(platform)
+        .unwrap()
+        .translation
+        .xz();
+
+    assert!(moved.length() > 2.0, "platform only moved {moved}");
+    assert!(
+        before.translation.distance(after.translation) < 0.1,
+        "slid from {} to {} on the platform",
+        before.translation,
+        after.translation
+    );
+    assert!(
+        before.rotation.angle_between(after.rotation) < 0.05,
+        "did not turn with the platform"
+    );
+}
+
+#[test]
+fn keeps_platform_velocity_after_jumping_off() {
+    let velocity = Vec3::new(4.0, 0.0, 0.0);
+    let (mut app, _, character) = setup(velocity, 0.0);
+    common::run(&mut app, 30);
+
+    app.send_input(KeyCode::Space);
+    common::run(&mut app, 10);
+    app.release_input(KeyCode::Space);
+
+    assert_eq!(
+        app.world.get::<ControllerState>(character),
+        Some(&ControllerState::Jump)
+    );
+    assert!(app
+        .world
+        .get::<ControllerPlatform>(character)
+        .unwrap()
+        .entity()
+        .is_none());
+    let momentum = app
+        .world
+        .get::<MovementController>(character)
+        .unwrap()
+        .forces()
+        .movement();
+    assert!(
+        momentum.distance(velocity) < 0.5,
+        "left the platform with {momentum}"
+    );
+}